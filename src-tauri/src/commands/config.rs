@@ -1,25 +1,163 @@
 use tauri::State;
+use std::path::Path;
 use std::sync::Arc;
 use crate::config::{AppConfig, ConfigManager, GeneralConfig, PreferenceConfig};
 use crate::core::logging::LogManager;
+use crate::core::hooks::tokenize_command;
+use crate::core::process::{validate_cookie_text, validate_date_subfolder_format, validate_format_sort, validate_proxy_url, validate_upload_date_format, COOKIE_KEYRINGS, THUMBNAIL_FORMATS};
+use crate::core::transcode::configured_ffmpeg_binary;
+use crate::core::util::parse_byte_size;
 
 #[tauri::command]
 pub fn get_app_config(config_manager: State<'_, Arc<ConfigManager>>) -> AppConfig {
     config_manager.get_config()
 }
 
+/// Rejects settings that would make every download fail.
+pub fn validate_general(config: &GeneralConfig) -> Result<(), String> {
+    if let Some(path) = config.ytdlp_config_path.as_deref().filter(|p| !p.trim().is_empty()) {
+        if !Path::new(path).is_file() {
+            return Err(format!("yt-dlp config file not found: {}", path));
+        }
+    }
+
+    // Either the binary or the folder holding it
+    if let Some(path) = config.ffmpeg_path.as_deref().filter(|p| !p.trim().is_empty()) {
+        let binary = configured_ffmpeg_binary(Path::new(path.trim()));
+        let is_ffmpeg = binary.file_stem().is_some_and(|s| s.to_string_lossy().to_ascii_lowercase().starts_with("ffmpeg"));
+        if !binary.is_file() || !is_ffmpeg {
+            return Err(format!("ffmpeg binary not found: {}", path.trim()));
+        }
+    }
+
+    if let Some(keyring) = config.cookies_browser_keyring.as_deref().filter(|k| !k.trim().is_empty()) {
+        if !COOKIE_KEYRINGS.contains(&keyring.trim()) {
+            return Err(format!("Unknown cookie keyring '{}'. Use one of: {}.", keyring, COOKIE_KEYRINGS.join(", ")));
+        }
+    }
+
+    if let Some(text) = config.cookies_text.as_deref().filter(|t| !t.trim().is_empty()) {
+        validate_cookie_text(text)?;
+    }
+
+    if config.subtitle_langs.trim().is_empty() || config.subtitle_langs.contains(char::is_whitespace) {
+        return Err("Subtitle languages must be a comma-separated list without spaces, e.g. en.*,de".into());
+    }
+
+    if let Some(sort) = config.format_sort.as_deref().filter(|s| !s.trim().is_empty()) {
+        validate_format_sort(sort)?;
+    }
+
+    if let Some(fmt) = config.date_subfolder_format.as_deref().filter(|f| !f.trim().is_empty()) {
+        validate_date_subfolder_format(fmt)?;
+    }
+
+    if let Some(fmt) = config.upload_date_format.as_deref().filter(|f| !f.trim().is_empty()) {
+        validate_upload_date_format(fmt)?;
+    }
+
+    if let Some(proxy) = config.proxy.as_deref().filter(|p| !p.trim().is_empty()) {
+        validate_proxy_url(proxy)?;
+    }
+
+    if let Some(size) = config.min_free_space.as_deref().filter(|s| !s.trim().is_empty()) {
+        if parse_byte_size(size).is_none() {
+            return Err(format!("Invalid minimum free space '{}'. Use values like 5G.", size));
+        }
+    }
+
+    if !config.download_path_pool.is_empty() && parse_byte_size(&config.download_path_pool_min_free).is_none() {
+        return Err(format!("Invalid free space threshold '{}'. Use values like 10G.", config.download_path_pool_min_free));
+    }
+
+    if let Some(limit) = config.rate_limit.as_deref().filter(|l| !l.trim().is_empty()) {
+        if parse_byte_size(limit).is_none() {
+            return Err(format!("Invalid rate limit '{}'. Use values like 500K or 2M.", limit));
+        }
+    }
+
+    if !(config.channel_include_videos || config.channel_include_shorts || config.channel_include_streams) {
+        return Err("Select at least one channel tab (videos, shorts or streams).".into());
+    }
+
+    if let Some(format) = config.thumbnail_format.as_deref().filter(|f| !f.trim().is_empty()) {
+        if !THUMBNAIL_FORMATS.contains(&format.trim()) {
+            return Err(format!("Unsupported thumbnail format '{}'. Use one of: {}.", format, THUMBNAIL_FORMATS.join(", ")));
+        }
+    }
+
+    if config.fragment_retries > 1000 {
+        return Err(format!("Fragment retries must be between 0 and 1000 (got {}).", config.fragment_retries));
+    }
+
+    if let Some(size) = config.buffer_size.as_deref().filter(|s| !s.trim().is_empty()) {
+        match parse_byte_size(size) {
+            Some(bytes) if (1024..=64 * 1024 * 1024).contains(&bytes) => {}
+            _ => return Err(format!("Invalid buffer size '{}'. Use a value between 1K and 64M, e.g. 16K.", size)),
+        }
+    }
+
+    if !(1..=16).contains(&config.concurrent_fragments) {
+        return Err(format!("Concurrent fragments must be between 1 and 16 (got {}).", config.concurrent_fragments));
+    }
+
+    if let Some(size) = config.http_chunk_size.as_deref().filter(|s| !s.trim().is_empty()) {
+        match parse_byte_size(size) {
+            Some(bytes) if (64 * 1024..=1024 * 1024 * 1024).contains(&bytes) => {}
+            _ => return Err(format!("Invalid HTTP chunk size '{}'. Use a value between 64K and 1G, e.g. 10M.", size)),
+        }
+    }
+
+    if config.enable_http_api {
+        if config.http_api_bind.trim().parse::<std::net::SocketAddr>().is_err() {
+            return Err(format!("Invalid HTTP API address '{}'. Use host:port, e.g. 127.0.0.1:8765.", config.http_api_bind));
+        }
+        if config.http_api_token.as_deref().map_or(0, |t| t.trim().len()) < 16 {
+            return Err("The HTTP API needs a token of at least 16 characters.".to_string());
+        }
+    }
+
+    if let Some(hook) = config.post_hook_command.as_deref().filter(|h| !h.trim().is_empty()) {
+        tokenize_command(hook)?;
+    }
+
+    let mut claimed_hours = [false; 24];
+    for window in &config.rate_schedule {
+        if window.start_hour > 23 || window.end_hour > 24 || window.start_hour == window.end_hour {
+            return Err(format!("Invalid rate window {}:00-{}:00.", window.start_hour, window.end_hour));
+        }
+        if let Some(limit) = window.limit.as_deref().filter(|l| !l.trim().is_empty()) {
+            if parse_byte_size(limit).is_none() {
+                return Err(format!("Invalid rate limit '{}' in schedule.", limit));
+            }
+        }
+        for hour in (0..24).filter(|h| window.contains(*h)) {
+            if claimed_hours[hour as usize] {
+                return Err(format!("Rate window {}:00-{}:00 overlaps another window.", window.start_hour, window.end_hour));
+            }
+            claimed_hours[hour as usize] = true;
+        }
+    }
+
+    Ok(())
+}
+
 #[tauri::command]
 pub fn save_general_config(
     config_manager: State<'_, Arc<ConfigManager>>,
     log_manager: State<'_, LogManager>, // NEW: Inject LogManager
     config: GeneralConfig
 ) -> Result<(), String> {
+    validate_general(&config)?;
+
     // 1. Update Log Level immediately
     if let Err(e) = log_manager.set_level(&config.log_level) {
         eprintln!("Failed to update log level: {}", e);
         // Don't fail the save just because logging failed to update, but warn
     }
 
+    crate::core::crash::set_reports_enabled(config.write_crash_reports);
+
     // 2. Save to Disk
     config_manager.update_general(config);
     config_manager.save()
@@ -32,4 +170,118 @@ pub fn save_preference_config(
 ) -> Result<(), String> {
     config_manager.update_preferences(config);
     config_manager.save()
-}
\ No newline at end of file
+}
+
+/// Clears cookies (file path and pasted text), the HTTP API token and a proxy carrying credentials.
+pub fn strip_secrets(config: &mut AppConfig) {
+    config.general.cookies_path = None;
+    config.general.cookies_text = None;
+    config.general.http_api_token = None;
+    if config.general.proxy.as_deref().is_some_and(|p| p.contains('@')) {
+        config.general.proxy = None;
+    }
+}
+
+/// Serializes the full settings for moving to another machine. Secrets (see `strip_secrets`)
+/// are left out unless explicitly requested.
+#[tauri::command]
+pub fn export_settings(
+    config_manager: State<'_, Arc<ConfigManager>>,
+    include_secrets: Option<bool>,
+) -> Result<String, String> {
+    let mut config = config_manager.get_config();
+    if !include_secrets.unwrap_or(false) {
+        strip_secrets(&mut config);
+    }
+    serde_json::to_string_pretty(&config).map_err(|e| format!("Serialization error: {}", e))
+}
+
+/// Merges an exported (or partial) settings file over the current config and saves it.
+/// Paths that don't exist on this machine are dropped; the returned list tells the UI which.
+#[tauri::command]
+pub fn import_settings(
+    config_manager: State<'_, Arc<ConfigManager>>,
+    log_manager: State<'_, LogManager>,
+    json: String,
+) -> Result<Vec<String>, String> {
+    let overlay: serde_json::Value = serde_json::from_str(&json)
+        .map_err(|e| format!("Settings file is not valid JSON: {}", e))?;
+    let mut config = config_manager.merge_over_current(&overlay)?;
+    let mut warnings = Vec::new();
+
+    let general = &mut config.general;
+    for folder in [&mut general.download_path, &mut general.audio_download_path, &mut general.video_download_path] {
+        if folder.as_deref().is_some_and(|p| !Path::new(p).is_dir()) {
+            warnings.push(format!("Download folder not found, using default: {}", folder.take().unwrap_or_default()));
+        }
+    }
+    if general.cookies_path.as_deref().is_some_and(|p| !Path::new(p).is_file()) {
+        warnings.push(format!("Cookies file not found, ignoring: {}", general.cookies_path.take().unwrap_or_default()));
+    }
+    if general.ytdlp_config_path.as_deref().is_some_and(|p| !Path::new(p).is_file()) {
+        warnings.push(format!("yt-dlp config file not found, ignoring: {}", general.ytdlp_config_path.take().unwrap_or_default()));
+    }
+    if general.ffmpeg_path.as_deref().is_some_and(|p| !configured_ffmpeg_binary(Path::new(p.trim())).is_file()) {
+        warnings.push(format!("ffmpeg binary not found, using the default: {}", general.ffmpeg_path.take().unwrap_or_default()));
+    }
+
+    validate_general(&config.general)?;
+
+    if let Err(e) = log_manager.set_level(&config.general.log_level) {
+        warnings.push(e);
+    }
+
+    config_manager.replace(config);
+    config_manager.save()?;
+    Ok(warnings)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RateWindow;
+
+    fn with_schedule(windows: &[(u32, u32)]) -> GeneralConfig {
+        GeneralConfig {
+            rate_schedule: windows.iter()
+                .map(|&(start_hour, end_hour)| RateWindow { start_hour, end_hour, limit: Some("1M".into()) })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn adjacent_windows_do_not_overlap() {
+        assert!(validate_general(&with_schedule(&[(9, 17), (17, 22), (22, 9)])).is_ok());
+    }
+
+    #[test]
+    fn overlapping_windows_are_rejected() {
+        assert!(validate_general(&with_schedule(&[(9, 17), (16, 20)])).is_err());
+        // Wrapping window overlapping one after midnight
+        assert!(validate_general(&with_schedule(&[(22, 6), (5, 8)])).is_err());
+    }
+
+    #[test]
+    fn empty_or_out_of_range_windows_are_rejected() {
+        assert!(validate_general(&with_schedule(&[(8, 8)])).is_err());
+        assert!(validate_general(&with_schedule(&[(24, 2)])).is_err());
+    }
+
+
+    #[test]
+    fn ffmpeg_path_accepts_binary_or_its_folder() {
+        let dir = std::env::temp_dir().join(format!("multiyt-dlp-ffmpeg-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let binary = dir.join(if cfg!(windows) { "ffmpeg.exe" } else { "ffmpeg" });
+        std::fs::write(&binary, b"").unwrap();
+        let with_ffmpeg = |path: &Path| GeneralConfig { ffmpeg_path: Some(path.to_string_lossy().to_string()), ..Default::default() };
+
+        assert!(validate_general(&with_ffmpeg(&binary)).is_ok());
+        assert!(validate_general(&with_ffmpeg(&dir)).is_ok());
+        assert!(validate_general(&with_ffmpeg(&dir.join("missing"))).is_err());
+
+        std::fs::remove_file(&binary).unwrap();
+        assert!(validate_general(&with_ffmpeg(&dir)).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
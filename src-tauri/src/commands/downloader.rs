@@ -1,50 +1,135 @@
-use tauri::{State};
+use tauri::{AppHandle, ClipboardManager, Manager, State};
 use uuid::Uuid;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use futures_util::{stream, StreamExt};
+use tokio::sync::Semaphore;
+use once_cell::sync::Lazy;
+use regex::Regex;
 
+use crate::config::{AppConfig, ConfigManager, GeneralConfig};
+use crate::commands::system::ensure_writable_dir;
 use crate::core::{
     error::AppError,
     manager::{JobManagerHandle},
+    process::{build_cookie_args, build_format_args, describe_format_args, info_json_path, new_configured_ytdlp_command, validate_proxy_url, CookieArgs, MIN_PREMIERE_CHECK_INTERVAL},
+    thumbnails,
+    transcode::EXTERNAL_SUBTITLE_FORMATS,
+    util::parse_byte_size,
 };
-use crate::models::{DownloadFormatPreset, QueuedJob, PlaylistResult, PlaylistEntry};
+use crate::models::{BatchEstimate, CompletedEntry, DownloadFormatPreset, DownloadProgressPayload, FormatOptions, FormatPreview, FormatSelectorPreview, JobPriority, JobSearchResult, QueuedJob, PlaylistResult, PlaylistEntry, StartDownloadOptions, VideoDetails, METADATA_OVERRIDE_FIELDS};
 
-// Helper: Probes the URL to see if it's a playlist or single video
-fn probe_url(url: &str) -> Result<Vec<PlaylistEntry>, AppError> {
-    let mut cmd = Command::new("yt-dlp");
-    cmd.arg("--flat-playlist")
-       .arg("--dump-single-json")
-       .arg("--no-warnings")
-       .arg(url);
+static UNAVAILABLE_TITLE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\[(Deleted|Private|Unavailable) video\]$").unwrap()
+});
 
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        cmd.creation_flags(0x08000000);
-    }
+/// A YouTube channel URL, optionally already pointing at one of its tabs.
+static YOUTUBE_CHANNEL_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?P<base>https?://(?:www\.|m\.)?youtube\.com/(?:@[^/?#]+|channel/[^/?#]+|c/[^/?#]+|user/[^/?#]+))(?:/(?P<tab>[^/?#]*))?/?(?:[?#].*)?$").unwrap()
+});
 
-    let output = cmd.output().map_err(|e| AppError::IoError(e.to_string()))?;
+/// Limiter for playlist/URL probes (yt-dlp --flat-playlist), sized by `max_concurrent_probes`.
+/// Rebuilt when the setting changes; probes already running keep their permit on the old one.
+static PROBE_SLOTS: Lazy<Mutex<(usize, Arc<Semaphore>)>> = Lazy::new(|| Mutex::new((0, Arc::new(Semaphore::new(0)))));
 
-    if !output.status.success() {
-        return Err(AppError::ProcessFailed { 
-            exit_code: output.status.code().unwrap_or(-1), 
-            stderr: String::from_utf8_lossy(&output.stderr).to_string() 
-        });
+fn probe_slots(limit: u32) -> Arc<Semaphore> {
+    let limit = limit.max(1) as usize;
+    let mut slots = PROBE_SLOTS.lock().unwrap();
+    if slots.0 != limit {
+        *slots = (limit, Arc::new(Semaphore::new(limit)));
     }
+    slots.1.clone()
+}
+
+/// Max yt-dlp processes spawned at once while estimating a batch.
+const ESTIMATE_PROBE_CONCURRENCY: usize = 3;
+
+/// Output layout for music-library mode; the defaults keep untagged tracks together instead of in "NA" folders.
+const MUSIC_LIBRARY_TEMPLATE: &str = "%(artist|Unknown Artist)s/%(album|Unknown Album)s/%(track_number|0)02d - %(title)s.%(ext)s";
+
+/// Longest label `set_job_label` accepts, in characters.
+const MAX_JOB_LABEL_LEN: usize = 64;
+
+/// Validation before handing a URL to yt-dlp: only http(s) is accepted. Hosts aren't checked,
+/// so `localhost` or `http://nas:8080` work; whether an extractor handles it is only known once yt-dlp runs.
+fn is_supported_url(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// Stricter heuristic for clipboard suggestions, where most copies are not meant as downloads:
+/// http(s) with a dotted host and no whitespace.
+fn looks_like_download_url(url: &str) -> bool {
+    let Some(rest) = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://")) else { return false; };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = host.rsplit('@').next().unwrap_or(host);
+    !url.contains(char::is_whitespace) && host.contains('.') && !host.starts_with('.') && !host.ends_with('.')
+}
+
+/// Last URL offered from the clipboard, so the same copy isn't suggested on every focus.
+static LAST_CLIPBOARD_SUGGESTION: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+/// Picks the thumbnail URL from probe JSON: the `thumbnail` field, else the last
+/// (largest) entry of `thumbnails`, which is all flat playlist entries carry.
+fn thumbnail_from_json(value: &serde_json::Value) -> Option<String> {
+    value.get("thumbnail").and_then(|t| t.as_str())
+        .or_else(|| {
+            value.get("thumbnails")
+                .and_then(|t| t.as_array())
+                .and_then(|arr| arr.iter().rev().find_map(|t| t.get("url").and_then(|u| u.as_str())))
+        })
+        .map(|s| s.to_string())
+}
+
+/// Extractor name from probe JSON: `extractor_key` ("Youtube", "Generic"), else `extractor`.
+/// Flat playlist entries only carry `ie_key`.
+fn extractor_from_json(value: &serde_json::Value) -> Option<String> {
+    ["extractor_key", "extractor", "ie_key"].iter()
+        .find_map(|key| value.get(*key).and_then(|s| s.as_str()))
+        .map(|s| s.to_string())
+}
 
-    let json_str = String::from_utf8_lossy(&output.stdout);
+/// Keeps entries `start..=end` (1-based, either bound optional). Bounds past the end are clamped.
+fn slice_entries(entries: Vec<PlaylistEntry>, start: Option<u32>, end: Option<u32>) -> Vec<PlaylistEntry> {
+    let skip = start.map_or(0, |s| s.saturating_sub(1) as usize);
+    let take = end.map_or(usize::MAX, |e| (e as usize).saturating_sub(skip));
+    entries.into_iter().skip(skip).take(take).collect()
+}
+
+// Helper: Parses the probe output to see if the URL is a playlist or single video
+fn parse_probe_output(url: &str, stdout: &[u8]) -> Result<PlaylistResult, AppError> {
+    let json_str = String::from_utf8_lossy(stdout);
     let parsed: serde_json::Value = serde_json::from_str(&json_str)
         .map_err(|e| AppError::ValidationFailed(format!("Failed to parse JSON: {}", e)))?;
 
     let mut entries = Vec::new();
+    let mut unavailable = Vec::new();
+    let mut title = None;
+    let extractor = extractor_from_json(&parsed);
 
     if let Some(entries_arr) = parsed.get("entries").and_then(|e| e.as_array()) {
-        for entry in entries_arr {
-            if let Some(u) = entry.get("url").and_then(|s| s.as_str()) {
-                entries.push(PlaylistEntry {
-                    id: entry.get("id").and_then(|s| s.as_str()).map(|s| s.to_string()),
-                    url: u.to_string(),
-                    title: entry.get("title").and_then(|s| s.as_str()).unwrap_or("Unknown").to_string(),
-                });
+        title = parsed.get("title").and_then(|s| s.as_str()).map(|s| s.to_string());
+        for (index, entry) in entries_arr.iter().enumerate() {
+            let title = entry.get("title").and_then(|s| s.as_str());
+            let url = entry.get("url").and_then(|s| s.as_str()).filter(|u| !u.is_empty());
+
+            match url {
+                // Removed videos keep a placeholder title such as "[Deleted video]" / "[Private video]"
+                Some(u) if !title.is_some_and(|t| UNAVAILABLE_TITLE_REGEX.is_match(t)) => {
+                    entries.push(PlaylistEntry {
+                        id: entry.get("id").and_then(|s| s.as_str()).map(|s| s.to_string()),
+                        url: u.to_string(),
+                        title: title.unwrap_or("Unknown").to_string(),
+                        thumbnail: thumbnail_from_json(entry),
+                        extractor: extractor_from_json(entry).or_else(|| extractor.clone()),
+                    });
+                }
+                _ => {
+                    let label = title
+                        .or_else(|| entry.get("id").and_then(|s| s.as_str()))
+                        .map(|s| s.to_string())
+                        .unwrap_or_else(|| format!("Entry {}", index + 1));
+                    unavailable.push(label);
+                }
             }
         }
     } else {
@@ -52,34 +137,552 @@ fn probe_url(url: &str) -> Result<Vec<PlaylistEntry>, AppError> {
             id: parsed.get("id").and_then(|s| s.as_str()).map(|s| s.to_string()),
             url: parsed.get("webpage_url").and_then(|s| s.as_str()).unwrap_or(url).to_string(),
             title: parsed.get("title").and_then(|s| s.as_str()).unwrap_or("Unknown").to_string(),
+            thumbnail: thumbnail_from_json(&parsed),
+            extractor: extractor.clone(),
+        });
+    }
+
+    Ok(PlaylistResult { title, entries, unavailable, extractor })
+}
+
+/// Probes `url` with yt-dlp (flat playlist, with the configured proxy and cookies), holding
+/// a permit so at most `max_concurrent_probes` run at once. The output is parsed on the
+/// blocking pool so a huge playlist doesn't stall other commands.
+async fn probe_url(url: &str, bin_dir: &Path, config: &GeneralConfig) -> Result<PlaylistResult, AppError> {
+    let _permit = probe_slots(config.max_concurrent_probes).acquire_owned().await
+        .map_err(|_| AppError::IoError("Probe limiter closed".into()))?;
+
+    let cookies = build_cookie_args(config);
+    let output = new_configured_ytdlp_command(bin_dir, config, None, &cookies)
+        .arg("--flat-playlist")
+        .arg("--dump-single-json")
+        .arg("--no-warnings")
+        .arg(url)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(AppError::ProcessFailed { 
+            exit_code: output.status.code().unwrap_or(-1), 
+            stderr: String::from_utf8_lossy(&output.stderr).to_string() 
         });
     }
 
-    Ok(entries)
+    let url = url.to_string();
+    tauri::async_runtime::spawn_blocking(move || parse_probe_output(&url, &output.stdout))
+        .await
+        .map_err(|e| AppError::IoError(format!("Probe task failed: {}", e)))?
+}
+
+/// Where the managed yt-dlp/ffmpeg binaries live.
+fn bin_dir(app_handle: &AppHandle) -> Result<PathBuf, AppError> {
+    app_handle.path_resolver().app_data_dir()
+        .map(|dir| dir.join("bin"))
+        .ok_or_else(|| AppError::IoError("Failed to resolve app data dir".into()))
+}
+
+/// Tab URLs to probe for a bare channel URL (no tab, or the home/featured page), per the
+/// channel_include_* settings. `None` for anything else, including a URL naming one tab.
+fn channel_tab_urls(url: &str, config: &GeneralConfig) -> Option<Vec<String>> {
+    let caps = YOUTUBE_CHANNEL_REGEX.captures(url.trim())?;
+    if !matches!(caps.name("tab").map(|t| t.as_str()), None | Some("") | Some("featured")) {
+        return None;
+    }
+    let base = &caps["base"];
+    let tabs = [
+        (config.channel_include_videos, "videos"),
+        (config.channel_include_shorts, "shorts"),
+        (config.channel_include_streams, "streams"),
+    ];
+    Some(tabs.iter().filter(|(on, _)| *on).map(|(_, tab)| format!("{}/{}", base, tab)).collect())
+}
+
+/// `probe_url`, except that a bare channel URL is expanded tab by tab (see `channel_tab_urls`)
+/// instead of yielding the tabs themselves as entries. Tabs the channel lacks are skipped.
+async fn probe_with_channel_tabs(url: &str, bin_dir: &Path, config: &GeneralConfig) -> Result<PlaylistResult, AppError> {
+    let Some(tab_urls) = channel_tab_urls(url, config) else {
+        return probe_url(url, bin_dir, config).await;
+    };
+
+    let mut merged = PlaylistResult { title: None, entries: Vec::new(), unavailable: Vec::new(), extractor: None };
+    let mut last_error = None;
+    for tab_url in tab_urls {
+        match probe_url(&tab_url, bin_dir, config).await {
+            Ok(tab) => {
+                // Tab playlists are titled "<channel> - Videos"
+                if merged.title.is_none() {
+                    merged.title = tab.title.map(|t| t.rsplit_once(" - ").map_or(t.clone(), |(channel, _)| channel.to_string()));
+                }
+                merged.extractor = merged.extractor.or(tab.extractor);
+                merged.entries.extend(tab.entries);
+                merged.unavailable.extend(tab.unavailable);
+            }
+            Err(e) => {
+                tracing::info!("Skipping channel tab {}: {}", tab_url, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    match last_error {
+        Some(e) if merged.entries.is_empty() && merged.unavailable.is_empty() => Err(e),
+        _ => Ok(merged),
+    }
 }
 
 #[tauri::command]
-pub async fn expand_playlist(url: String) -> Result<PlaylistResult, AppError> {
-    let entries = probe_url(&url)?;
-    Ok(PlaylistResult { entries })
+pub async fn expand_playlist(
+    app_handle: AppHandle,
+    config_manager: State<'_, Arc<ConfigManager>>,
+    url: String,
+) -> Result<PlaylistResult, AppError> {
+    probe_with_channel_tabs(&url, &bin_dir(&app_handle)?, &config_manager.get_config().general).await
 }
 
+/// Thumbnail for a single video. Returns the remote image URL, or with `inline` a data URI
+/// (cached on disk by video id) so the queue can show it offline.
 #[tauri::command]
-pub async fn start_download(
+pub async fn get_thumbnail(
+    app_handle: AppHandle,
+    config_manager: State<'_, Arc<ConfigManager>>,
     url: String,
-    download_path: Option<String>,
+    inline: Option<bool>,
+) -> Result<String, AppError> {
+    let entry = probe_url(&url, &bin_dir(&app_handle)?, &config_manager.get_config().general).await?.entries.into_iter().next()
+        .ok_or_else(|| AppError::ValidationFailed("Nothing found at this URL.".into()))?;
+    let thumbnail_url = entry.thumbnail
+        .ok_or_else(|| AppError::ValidationFailed("No thumbnail available for this video.".into()))?;
+
+    if !inline.unwrap_or(false) {
+        return Ok(thumbnail_url);
+    }
+    thumbnails::fetch_inline(&thumbnail_url, entry.id.as_deref()).await
+        .map_err(AppError::IoError)
+}
+
+/// Resolves the direct media URL(s) for `url` without downloading anything.
+/// Presets that merge separate streams return two URLs (video first, then audio).
+/// The URLs are signed by the host and expire, typically within a few hours.
+#[tauri::command]
+pub async fn get_stream_url(
+    app_handle: AppHandle,
+    config_manager: State<'_, Arc<ConfigManager>>,
+    url: String,
+    format_preset: DownloadFormatPreset,
+    video_resolution: Option<String>,
+) -> Result<Vec<String>, AppError> {
+    if !is_supported_url(&url) {
+        return Err(AppError::ValidationFailed("Invalid URL provided.".into()));
+    }
+
+    let app_dir = app_handle.path_resolver().app_data_dir()
+        .ok_or_else(|| AppError::IoError("Failed to resolve app data dir".into()))?;
+    let general_config = config_manager.get_config().general;
+    let resolution = video_resolution.unwrap_or_else(|| "best".to_string());
+
+    let cookies = build_cookie_args(&general_config);
+    let mut cmd = new_configured_ytdlp_command(&app_dir.join("bin"), &general_config, None, &cookies);
+    cmd.args(build_format_args(&FormatOptions::new(format_preset, resolution), &general_config))
+        .arg("--get-url")
+        .arg("--no-playlist")
+        .arg("--no-warnings")
+        .arg(&url);
+
+    let output = cmd.output().await?;
+
+    if !output.status.success() {
+        return Err(AppError::ProcessFailed {
+            exit_code: output.status.code().unwrap_or(-1),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string()
+        });
+    }
+
+    let urls: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| l.starts_with("http"))
+        .map(|l| l.to_string())
+        .collect();
+
+    if urls.is_empty() {
+        return Err(AppError::ValidationFailed("yt-dlp returned no media URL.".into()));
+    }
+
+    Ok(urls)
+}
+
+fn validate_target_filesize(target_filesize: &Option<String>) -> Result<(), AppError> {
+    if let Some(target) = target_filesize.as_deref().filter(|t| !t.trim().is_empty()) {
+        if parse_byte_size(target).is_none() {
+            return Err(AppError::ValidationFailed(format!("Invalid target file size '{}'. Use values like 500M or 1.5G.", target)));
+        }
+    }
+    Ok(())
+}
+
+fn validate_audio_lang(audio_lang: &Option<String>) -> Result<(), AppError> {
+    if let Some(lang) = audio_lang.as_deref().map(str::trim).filter(|l| !l.is_empty()) {
+        if !lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(AppError::ValidationFailed(format!("Invalid audio language '{}'.", lang)));
+        }
+    }
+    Ok(())
+}
+
+/// Lists the distinct audio track languages offered for a single video, for the language picker.
+#[tauri::command]
+pub async fn get_audio_languages(
+    app_handle: AppHandle,
+    config_manager: State<'_, Arc<ConfigManager>>,
+    url: String,
+) -> Result<Vec<String>, AppError> {
+    if !is_supported_url(&url) {
+        return Err(AppError::ValidationFailed("Invalid URL provided.".into()));
+    }
+
+    let app_dir = app_handle.path_resolver().app_data_dir()
+        .ok_or_else(|| AppError::IoError("Failed to resolve app data dir".into()))?;
+    let general_config = config_manager.get_config().general;
+
+    let cookies = build_cookie_args(&general_config);
+    let mut cmd = new_configured_ytdlp_command(&app_dir.join("bin"), &general_config, None, &cookies);
+    cmd.arg("--dump-json")
+        .arg("--no-playlist")
+        .arg("--no-warnings")
+        .arg(&url);
+
+    let output = cmd.output().await?;
+
+    if !output.status.success() {
+        return Err(AppError::ProcessFailed {
+            exit_code: output.status.code().unwrap_or(-1),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string()
+        });
+    }
+
+    let info: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| AppError::ValidationFailed(format!("Failed to parse JSON: {}", e)))?;
+
+    let mut languages: Vec<String> = info.get("formats")
+        .and_then(|f| f.as_array())
+        .map(|formats| formats.iter()
+            .filter(|f| f.get("acodec").and_then(|a| a.as_str()) != Some("none"))
+            .filter_map(|f| f.get("language").and_then(|l| l.as_str()))
+            .map(|l| l.to_string())
+            .collect())
+        .unwrap_or_default();
+    languages.sort();
+    languages.dedup();
+
+    Ok(languages)
+}
+
+/// Simulates the download and reports which format yt-dlp would pick, so the
+/// effect of resolution caps and size targets can be confirmed before queueing.
+#[tauri::command]
+pub async fn preview_format(
+    app_handle: AppHandle,
+    config_manager: State<'_, Arc<ConfigManager>>,
+    url: String,
+    format_preset: DownloadFormatPreset,
+    video_resolution: Option<String>,
+    target_filesize: Option<String>,
+) -> Result<FormatPreview, AppError> {
+    if !is_supported_url(&url) {
+        return Err(AppError::ValidationFailed("Invalid URL provided.".into()));
+    }
+    validate_target_filesize(&target_filesize)?;
+
+    let app_dir = app_handle.path_resolver().app_data_dir()
+        .ok_or_else(|| AppError::IoError("Failed to resolve app data dir".into()))?;
+    let general_config = config_manager.get_config().general;
+
+    let mut opts = FormatOptions::new(format_preset, video_resolution.unwrap_or_else(|| "best".to_string()));
+    opts.target_filesize = target_filesize;
+
+    let cookies = build_cookie_args(&general_config);
+    let mut cmd = new_configured_ytdlp_command(&app_dir.join("bin"), &general_config, None, &cookies);
+    cmd.args(build_format_args(&opts, &general_config))
+        .arg("--dump-json")
+        .arg("--no-playlist")
+        .arg("--no-warnings")
+        .arg(&url);
+
+    let output = cmd.output().await?;
+
+    if !output.status.success() {
+        return Err(AppError::ProcessFailed {
+            exit_code: output.status.code().unwrap_or(-1),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string()
+        });
+    }
+
+    let info: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| AppError::ValidationFailed(format!("Failed to parse JSON: {}", e)))?;
+
+    Ok(FormatPreview {
+        format_id: info.get("format_id").and_then(|v| v.as_str()).unwrap_or("unknown").to_string(),
+        format: info.get("format").and_then(|v| v.as_str()).unwrap_or("unknown").to_string(),
+        filesize: info.get("filesize")
+            .or_else(|| info.get("filesize_approx"))
+            .and_then(|v| v.as_f64())
+            .map(|v| v as u64),
+    })
+}
+
+/// The `-f`/`-S` arguments a download with these options would use, and what they mean, without
+/// touching the network. `general` previews unsaved settings; the saved config applies when omitted.
+#[tauri::command]
+pub async fn preview_format_selector(
+    config_manager: State<'_, Arc<ConfigManager>>,
     format_preset: DownloadFormatPreset,
-    video_resolution: String, 
+    video_resolution: Option<String>,
+    target_filesize: Option<String>,
+    audio_lang: Option<String>,
+    audio_bitrate: Option<u32>,
+    general: Option<GeneralConfig>,
+) -> Result<FormatSelectorPreview, AppError> {
+    validate_target_filesize(&target_filesize)?;
+    validate_audio_lang(&audio_lang)?;
+    let general = general.unwrap_or_else(|| config_manager.get_config().general);
+
+    let mut opts = FormatOptions::new(format_preset, video_resolution.unwrap_or_else(|| "best".to_string()));
+    opts.target_filesize = target_filesize;
+    opts.audio_lang = audio_lang;
+    opts.audio_bitrate = audio_bitrate;
+
+    let args = build_format_args(&opts, &general);
+    let value_of = |flag: &str| args.iter().rposition(|a| a == flag).and_then(|i| args.get(i + 1)).cloned();
+    Ok(FormatSelectorPreview {
+        format: value_of("-f"),
+        sort: value_of("-S"),
+        explanation: describe_format_args(&opts, &general),
+        args,
+    })
+}
+
+/// Probes each URL (including playlist entries) with the selected format and returns
+/// the approximate size of every entry; `None` means yt-dlp could not tell.
+async fn probe_entry_sizes(bin_dir: &Path, config: &GeneralConfig, cookies: &CookieArgs, format_args: &[String], url: String) -> Vec<Option<u64>> {
+    let mut cmd = new_configured_ytdlp_command(bin_dir, config, None, cookies);
+    cmd.args(format_args)
+        .arg("--dump-json")
+        .arg("--no-warnings")
+        .arg(&url);
+
+    let output = match cmd.output().await {
+        Ok(o) => o,
+        Err(_) => return vec![None],
+    };
+
+    let sizes: Vec<Option<u64>> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .map(|info| {
+            info.get("filesize")
+                .or_else(|| info.get("filesize_approx"))
+                .and_then(|v| v.as_f64())
+                .filter(|v| *v > 0.0)
+                .map(|v| v as u64)
+        })
+        .collect();
+
+    // A failed probe still counts as one entry of unknown size
+    if sizes.is_empty() { vec![None] } else { sizes }
+}
+
+/// Preflight estimate of a batch's total size and download time.
+/// Time is derived from this session's average per-download speed multiplied by the
+/// number of parallel slots, so it is only available after a download has run.
+#[tauri::command]
+pub async fn estimate_batch(
+    app_handle: AppHandle,
+    config_manager: State<'_, Arc<ConfigManager>>,
+    manager: State<'_, JobManagerHandle>,
+    urls: Vec<String>,
+    format_preset: DownloadFormatPreset,
+    video_resolution: Option<String>,
+) -> Result<BatchEstimate, AppError> {
+    if let Some(bad) = urls.iter().find(|u| !is_supported_url(u)) {
+        return Err(AppError::ValidationFailed(format!("Invalid URL provided: {}", bad)));
+    }
+
+    let app_dir = app_handle.path_resolver().app_data_dir()
+        .ok_or_else(|| AppError::IoError("Failed to resolve app data dir".into()))?;
+    let bin_dir = app_dir.join("bin");
+    let general_config = config_manager.get_config().general;
+    let resolution = video_resolution.unwrap_or_else(|| "best".to_string());
+
+    let cookies = build_cookie_args(&general_config);
+    let format_args = build_format_args(&FormatOptions::new(format_preset, resolution), &general_config);
+
+    let sizes: Vec<Option<u64>> = stream::iter(urls)
+        .map(|url| probe_entry_sizes(&bin_dir, &general_config, &cookies, &format_args, url))
+        .buffer_unordered(ESTIMATE_PROBE_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+
+    let entry_count = sizes.len() as u32;
+    let total_bytes: u64 = sizes.iter().flatten().sum();
+    let incomplete_estimate = sizes.iter().any(|s| s.is_none());
+
+    let parallel = general_config.max_concurrent_downloads.min(entry_count).max(1) as f64;
+    let est_seconds = manager.average_speed().await
+        .map(|speed| (total_bytes as f64 / (speed * parallel)).ceil() as u64);
+
+    Ok(BatchEstimate { total_bytes, entry_count, est_seconds, incomplete_estimate })
+}
+
+/// Parses a preset name as stored in `PreferenceConfig` ("best_mp4", "audio_mp3", ...).
+fn parse_preset(name: &str) -> Option<DownloadFormatPreset> {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+}
+
+/// Options `queue_download` fills in from the config when the caller leaves them out.
+#[derive(Debug, PartialEq)]
+struct ResolvedDefaults {
+    format_preset: DownloadFormatPreset,
+    download_path: Option<String>,
+    video_resolution: String,
     embed_metadata: bool,
     embed_thumbnail: bool,
     filename_template: String,
-    restrict_filenames: Option<bool>,
-    manager: State<'_, JobManagerHandle>, 
-) -> Result<Vec<Uuid>, AppError> { 
-    
-    if !url.starts_with("http://") && !url.starts_with("https://") {
+}
+
+/// Explicit options win; otherwise the saved preferences (format, resolution, embedding)
+/// and general config (download paths, filename template) apply. Music library mode
+/// forces embedding and its own template.
+fn resolve_defaults(options: &StartDownloadOptions, config: &AppConfig) -> ResolvedDefaults {
+    let format_preset = options.format_preset.clone()
+        .or_else(|| parse_preset(&config.preferences.format_preset))
+        .unwrap_or(DownloadFormatPreset::Best);
+    let type_download_path = if format_preset.is_audio() {
+        config.general.audio_download_path.clone()
+    } else {
+        config.general.video_download_path.clone()
+    };
+    // Explicit path > per-type default > global default > OS media folder (resolved at download time)
+    let download_path = options.download_path.clone()
+        .or(type_download_path.filter(|p| !p.trim().is_empty()))
+        .or_else(|| config.general.download_path.clone());
+    let music_library = options.music_library.unwrap_or(false);
+    // Music library mode brings its own tagging, cover art and folder layout
+    let filename_template = if music_library {
+        MUSIC_LIBRARY_TEMPLATE.to_string()
+    } else {
+        options.filename_template.clone().unwrap_or_else(|| config.general.filename_template.clone())
+    };
+    ResolvedDefaults {
+        video_resolution: options.video_resolution.clone().unwrap_or_else(|| config.preferences.video_resolution.clone()),
+        embed_metadata: music_library || options.embed_metadata.unwrap_or(config.preferences.embed_metadata),
+        embed_thumbnail: music_library || options.embed_thumbnail.unwrap_or(config.preferences.embed_thumbnail),
+        format_preset,
+        download_path,
+        filename_template,
+    }
+}
+
+/// Queues `url` (expanding playlists). Every option may be omitted, in which case the
+/// saved preferences (format, resolution, embedding) and general config (download path,
+/// filename template) apply, so non-UI callers get the same defaults as the form.
+#[tauri::command]
+pub async fn start_download(
+    app_handle: AppHandle,
+    url: String,
+    options: StartDownloadOptions,
+) -> Result<Vec<Uuid>, AppError> {
+    queue_download(&app_handle, url, options).await
+}
+
+/// Validates the options and queues one job per (playlist) entry. Shared by the
+/// `start_download` command and the HTTP API.
+pub async fn queue_download(
+    app_handle: &AppHandle,
+    url: String,
+    options: StartDownloadOptions,
+) -> Result<Vec<Uuid>, AppError> {
+    let manager = app_handle.state::<JobManagerHandle>();
+    let config = app_handle.state::<Arc<ConfigManager>>().get_config();
+    let ResolvedDefaults {
+        format_preset, download_path, video_resolution, embed_metadata, embed_thumbnail, filename_template,
+    } = resolve_defaults(&options, &config);
+    let StartDownloadOptions {
+        restrict_filenames, split_audio_chapters, target_filesize, metadata_overrides,
+        write_source_url, download_storyboard, external_subtitle, transcode_profile,
+        write_comments, music_library, live_from_start, wait_for_premiere,
+        premiere_check_interval, priority, audio_lang, audio_bitrate, write_m3u, proxy,
+        start_paused, playlist_start, playlist_end, ..
+    } = options;
+
+    if !is_supported_url(&url) {
         return Err(AppError::ValidationFailed("Invalid URL provided.".into()));
     }
+    validate_target_filesize(&target_filesize)?;
+    validate_audio_lang(&audio_lang)?;
+    if let Some(kbps) = audio_bitrate {
+        if !(32..=320).contains(&kbps) {
+            return Err(AppError::ValidationFailed(format!("Audio bitrate must be between 32 and 320 kbps (got {}).", kbps)));
+        }
+    }
+
+    if let Some(secs) = premiere_check_interval {
+        if !(MIN_PREMIERE_CHECK_INTERVAL..=86400).contains(&secs) {
+            return Err(AppError::ValidationFailed(format!("Premiere check interval must be between {} and 86400 seconds (got {}).", MIN_PREMIERE_CHECK_INTERVAL, secs)));
+        }
+    }
+
+    if playlist_start == Some(0) || playlist_end == Some(0) {
+        return Err(AppError::ValidationFailed("Playlist positions start at 1.".into()));
+    }
+    if let (Some(start), Some(end)) = (playlist_start, playlist_end) {
+        if start > end {
+            return Err(AppError::ValidationFailed(format!("Playlist start ({}) is after the end ({}).", start, end)));
+        }
+    }
+
+    if let Some(profile) = &transcode_profile {
+        if profile.crf.is_some_and(|crf| crf > 63) {
+            return Err(AppError::ValidationFailed("Transcode CRF must be between 0 and 63.".into()));
+        }
+        if profile.max_height.is_some_and(|h| !(144..=4320).contains(&h)) {
+            return Err(AppError::ValidationFailed("Transcode height must be between 144 and 4320.".into()));
+        }
+        if profile.audio_bitrate.is_some_and(|kbps| !(32..=512).contains(&kbps)) {
+            return Err(AppError::ValidationFailed("Transcode audio bitrate must be between 32 and 512 kbps.".into()));
+        }
+    }
+
+    let external_subtitle = external_subtitle.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    if let Some(path) = &external_subtitle {
+        let path = Path::new(path);
+        if !path.is_file() {
+            return Err(AppError::ValidationFailed(format!("Subtitle file not found: {}", path.display())));
+        }
+        let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+        if !EXTERNAL_SUBTITLE_FORMATS.contains(&ext.as_str()) {
+            return Err(AppError::ValidationFailed(format!("Unsupported subtitle file. Use one of: {}.", EXTERNAL_SUBTITLE_FORMATS.join(", "))));
+        }
+    }
+
+    let proxy = proxy.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    if let Some(p) = &proxy {
+        validate_proxy_url(p).map_err(AppError::ValidationFailed)?;
+    }
+
+    let music_library = music_library.unwrap_or(false);
+    if music_library && !format_preset.is_audio() {
+        return Err(AppError::ValidationFailed("Music library mode needs an audio format.".into()));
+    }
+
+    let metadata_overrides: Vec<(String, String)> = metadata_overrides.unwrap_or_default()
+        .into_iter()
+        .filter(|(_, value)| !value.trim().is_empty())
+        .collect();
+    if let Some((field, _)) = metadata_overrides.iter().find(|(f, _)| !METADATA_OVERRIDE_FIELDS.contains(&f.as_str())) {
+        return Err(AppError::ValidationFailed(format!("Unsupported metadata field '{}'.", field)));
+    }
 
     let safe_template = if filename_template.trim().is_empty() {
         "%(title)s.%(ext)s".to_string()
@@ -90,10 +693,19 @@ pub async fn start_download(
         filename_template
     };
 
-    let entries = probe_url(&url)?;
+    let mut probed = probe_with_channel_tabs(&url, &bin_dir(app_handle)?, &config.general).await?;
+    probed.entries = slice_entries(probed.entries, playlist_start, playlist_end);
+    if probed.entries.is_empty() {
+        return Err(AppError::ValidationFailed("No playlist entries fall within the selected range.".into()));
+    }
+    if external_subtitle.is_some() && probed.entries.len() > 1 {
+        return Err(AppError::ValidationFailed("An external subtitle file can only be added to a single video, not a playlist.".into()));
+    }
+    let batch_id = Uuid::new_v4();
+    let batch_size = probed.entries.len() as u32;
     let mut created_job_ids = Vec::new();
 
-    for entry in entries {
+    for (batch_index, entry) in probed.entries.into_iter().enumerate() {
         let job_id = Uuid::new_v4();
         
         let job_data = QueuedJob {
@@ -106,17 +718,57 @@ pub async fn start_download(
             embed_thumbnail,
             filename_template: safe_template.clone(),
             restrict_filenames: restrict_filenames.unwrap_or(false),
+            split_audio_chapters: split_audio_chapters.unwrap_or(false),
+            target_filesize: target_filesize.clone(),
+            metadata_overrides: metadata_overrides.clone(),
+            write_source_url: write_source_url.unwrap_or(false),
+            download_storyboard: download_storyboard.unwrap_or(false),
+            external_subtitle: external_subtitle.clone(),
+            transcode_profile: transcode_profile.clone(),
+            write_comments: write_comments.unwrap_or(false),
+            music_library,
+            live_from_start: live_from_start.unwrap_or(false),
+            wait_for_premiere: wait_for_premiere.unwrap_or(false),
+            premiere_check_interval,
+            priority: priority.unwrap_or_default(),
+            video_id: entry.id,
+            audio_lang: audio_lang.clone(),
+            audio_bitrate,
+            batch_id: Some(batch_id),
+            batch_index: batch_index as u32,
+            batch_size,
+            batch_title: probed.title.clone(),
+            write_m3u: write_m3u.unwrap_or(false),
+            proxy: proxy.clone(),
+            title: Some(entry.title),
+            thumbnail: entry.thumbnail,
+            held: start_paused.unwrap_or(false),
+            label: None,
         };
 
-        manager.add_job(job_data).await
-            .map_err(|e| AppError::ValidationFailed(e))?;
-            
-        created_job_ids.push(job_id);
+        match manager.add_job(job_data).await {
+            Ok(()) => created_job_ids.push(job_id),
+            // Within a playlist, skip entries that are already queued instead of failing the rest
+            Err(AppError::JobAlreadyExists(dup)) if batch_size > 1 => {
+                tracing::info!("Skipping duplicate playlist entry {}", dup);
+            }
+            Err(e) => return Err(e),
+        }
     }
 
     Ok(created_job_ids)
 }
 
+/// Cancels every running and queued job from one `start_download` call (e.g. a playlist).
+/// Returns the ids that were cancelled.
+#[tauri::command]
+pub async fn cancel_batch(
+    batch_id: Uuid,
+    manager: State<'_, JobManagerHandle>,
+) -> Result<Vec<Uuid>, AppError> {
+    Ok(manager.cancel_batch(batch_id).await)
+}
+
 #[tauri::command]
 pub async fn cancel_download(
     job_id: Uuid,
@@ -126,6 +778,180 @@ pub async fn cancel_download(
     Ok(())
 }
 
+/// Re-probes a queued job's URL on its own and stores the real title/thumbnail; flat playlist
+/// expansion often only yields placeholders like "Unknown". Emits `queue-state` on success.
+#[tauri::command]
+pub async fn refresh_job_metadata(
+    app_handle: AppHandle,
+    id: Uuid,
+    config_manager: State<'_, Arc<ConfigManager>>,
+    manager: State<'_, JobManagerHandle>,
+) -> Result<PlaylistEntry, AppError> {
+    let url = manager.job_url(id).await
+        .ok_or_else(|| AppError::ValidationFailed(format!("Unknown job {}", id)))?;
+    let probed = probe_url(&url, &bin_dir(&app_handle)?, &config_manager.get_config().general).await?;
+    let entry = probed.entries.into_iter().next()
+        .ok_or_else(|| AppError::ValidationFailed("The probe returned no video for this job.".into()))?;
+    manager.update_job_metadata(id, entry.title.clone(), entry.thumbnail.clone()).await
+        .map_err(AppError::ValidationFailed)?;
+    Ok(entry)
+}
+
+/// Drops a finished, failed or cancelled job from the queue view and its persisted state.
+#[tauri::command]
+pub async fn remove_job(
+    job_id: Uuid,
+    manager: State<'_, JobManagerHandle>,
+) -> Result<(), AppError> {
+    manager.remove_job(job_id).await
+        .map_err(AppError::ValidationFailed)
+}
+
+/// Previously completed downloads of the video(s) behind `url`, in any format, so the UI
+/// can warn before fetching the same thing twice.
+#[tauri::command]
+pub async fn check_already_downloaded(
+    app_handle: AppHandle,
+    url: String,
+    config_manager: State<'_, Arc<ConfigManager>>,
+    manager: State<'_, JobManagerHandle>,
+) -> Result<Vec<CompletedEntry>, AppError> {
+    let video_ids: Vec<String> = probe_with_channel_tabs(&url, &bin_dir(&app_handle)?, &config_manager.get_config().general).await?.entries.into_iter().filter_map(|e| e.id).collect();
+    Ok(manager.completed_entries(video_ids).await)
+}
+
+/// Details for a completed download from the `.info.json` next to it (needs
+/// `write_info_json`). Returns `None` when there is no sidecar.
+#[tauri::command]
+pub async fn get_video_details(path: String) -> Result<Option<VideoDetails>, AppError> {
+    let info_path = info_json_path(Path::new(&path));
+    let content = match tokio::fs::read_to_string(&info_path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let json: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| AppError::ValidationFailed(format!("Unreadable info JSON: {}", e)))?;
+
+    let text = |key: &str| json.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+    Ok(Some(VideoDetails {
+        title: text("title"),
+        description: text("description"),
+        uploader: text("uploader").or_else(|| text("channel")),
+        upload_date: text("upload_date"),
+        duration: json.get("duration").and_then(|v| v.as_f64()),
+        view_count: json.get("view_count").and_then(|v| v.as_u64()),
+        like_count: json.get("like_count").and_then(|v| v.as_u64()),
+        tags: json.get("tags")
+            .and_then(|v| v.as_array())
+            .map(|tags| tags.iter().filter_map(|t| t.as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default(),
+        webpage_url: text("webpage_url"),
+    }))
+}
+
+/// Offers the clipboard contents as a download when it is a URL that isn't already queued
+/// and wasn't offered before. Only read when the UI asks (e.g. on window focus).
+#[tauri::command]
+pub async fn get_clipboard_url_suggestion(
+    app_handle: AppHandle,
+    manager: State<'_, JobManagerHandle>,
+) -> Result<Option<String>, AppError> {
+    let text = app_handle.clipboard_manager().read_text()
+        .map_err(|e| AppError::IoError(format!("Clipboard unavailable: {}", e)))?;
+    let Some(url) = text.map(|t| t.trim().to_string()).filter(|t| looks_like_download_url(t)) else {
+        return Ok(None);
+    };
+
+    if LAST_CLIPBOARD_SUGGESTION.lock().unwrap().as_deref() == Some(url.as_str()) {
+        return Ok(None);
+    }
+    if manager.is_url_active(url.clone()).await {
+        return Ok(None);
+    }
+    *LAST_CLIPBOARD_SUGGESTION.lock().unwrap() = Some(url.clone());
+    Ok(Some(url))
+}
+
+/// Releases jobs queued with `start_paused`: the given ones, or every held job when `job_ids`
+/// is omitted. Returns how many were released.
+#[tauri::command]
+pub async fn start_queue(job_ids: Option<Vec<Uuid>>, manager: State<'_, JobManagerHandle>) -> Result<u32, AppError> {
+    Ok(manager.start_queue(job_ids).await)
+}
+
+/// Tags a job with a free-form label (e.g. "work", "archive"); an empty label clears it.
+#[tauri::command]
+pub async fn set_job_label(id: Uuid, label: Option<String>, manager: State<'_, JobManagerHandle>) -> Result<(), AppError> {
+    let label = label.map(|l| l.trim().to_string()).filter(|l| !l.is_empty());
+    if label.as_ref().is_some_and(|l| l.chars().count() > MAX_JOB_LABEL_LEN) {
+        return Err(AppError::ValidationFailed(format!("Labels are limited to {} characters.", MAX_JOB_LABEL_LEN)));
+    }
+    manager.set_job_label(id, label).await
+        .map_err(AppError::ValidationFailed)
+}
+
+/// Jobs whose title, URL or label contains `query` (case-insensitive), for filtering large queues.
+#[tauri::command]
+pub async fn search_queue(query: String, manager: State<'_, JobManagerHandle>) -> Result<Vec<JobSearchResult>, AppError> {
+    Ok(manager.search_jobs(query).await)
+}
+
+/// Suspends every running download and stops new ones from starting until `resume_all`.
+#[tauri::command]
+pub async fn pause_all(manager: State<'_, JobManagerHandle>) -> Result<(), AppError> {
+    manager.pause_all().await;
+    Ok(())
+}
+
+/// Continues the downloads suspended by `pause_all` and restarts the queue.
+#[tauri::command]
+pub async fn resume_all(manager: State<'_, JobManagerHandle>) -> Result<(), AppError> {
+    manager.resume_all().await;
+    Ok(())
+}
+
+/// Restarts a queue that was halted after a failure (`stop_queue_on_error`).
+#[tauri::command]
+pub async fn resume_queue(manager: State<'_, JobManagerHandle>) -> Result<(), AppError> {
+    manager.resume_queue().await;
+    Ok(())
+}
+
+/// Redirects every job that hasn't started yet to `path`; running jobs are left alone.
+/// Returns the number of jobs changed.
+#[tauri::command]
+pub async fn set_queue_download_path(
+    path: String,
+    manager: State<'_, JobManagerHandle>,
+) -> Result<u32, AppError> {
+    let path = path.trim().to_string();
+    if path.is_empty() {
+        return Err(AppError::ValidationFailed("Download path is empty.".into()));
+    }
+    ensure_writable_dir(Path::new(&path)).map_err(AppError::ValidationFailed)?;
+    Ok(manager.set_queue_download_path(path).await)
+}
+
+/// Moves a still-queued job to another priority level (e.g. bump it to run next).
+#[tauri::command]
+pub async fn set_job_priority(
+    job_id: Uuid,
+    priority: JobPriority,
+    manager: State<'_, JobManagerHandle>,
+) -> Result<(), AppError> {
+    manager.set_priority(job_id, priority).await
+        .map_err(AppError::ValidationFailed)
+}
+
+/// Last known progress of every active job, so a reloaded webview can restore its bars.
+#[tauri::command]
+pub async fn get_current_progress(
+    manager: State<'_, JobManagerHandle>
+) -> Result<Vec<DownloadProgressPayload>, String> {
+    Ok(manager.current_progress().await)
+}
+
 #[tauri::command]
 pub async fn get_pending_jobs(manager: State<'_, JobManagerHandle>) -> Result<u32, String> {
     Ok(manager.get_pending_count().await)
@@ -142,4 +968,154 @@ pub async fn resume_pending_jobs(
 pub async fn clear_pending_jobs(manager: State<'_, JobManagerHandle>) -> Result<(), String> {
     manager.clear_pending().await;
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(count: usize) -> Vec<PlaylistEntry> {
+        (1..=count).map(|i| PlaylistEntry {
+            id: Some(i.to_string()),
+            url: format!("https://example.com/{}", i),
+            title: format!("Video {}", i),
+            thumbnail: None,
+            extractor: None,
+        }).collect()
+    }
+
+    fn ids(entries: &[PlaylistEntry]) -> Vec<&str> {
+        entries.iter().filter_map(|e| e.id.as_deref()).collect()
+    }
+
+    #[test]
+    fn slice_from_first_entry() {
+        assert_eq!(ids(&slice_entries(entries(5), Some(1), Some(3))), ["1", "2", "3"]);
+    }
+
+    #[test]
+    fn slice_end_past_length_is_clamped() {
+        assert_eq!(ids(&slice_entries(entries(5), Some(4), Some(50))), ["4", "5"]);
+    }
+
+    #[test]
+    fn slice_start_past_length_is_empty() {
+        assert!(slice_entries(entries(5), Some(6), None).is_empty());
+        assert!(slice_entries(entries(5), Some(9), Some(12)).is_empty());
+    }
+
+    #[test]
+    fn slice_single_entry_when_start_equals_end() {
+        assert_eq!(ids(&slice_entries(entries(5), Some(3), Some(3))), ["3"]);
+    }
+
+    #[test]
+    fn slice_with_either_bound_absent() {
+        assert_eq!(ids(&slice_entries(entries(5), None, None)), ["1", "2", "3", "4", "5"]);
+        assert_eq!(ids(&slice_entries(entries(5), Some(4), None)), ["4", "5"]);
+        assert_eq!(ids(&slice_entries(entries(5), None, Some(2))), ["1", "2"]);
+    }
+
+
+    fn configured() -> AppConfig {
+        let mut config = AppConfig::default();
+        config.preferences.format_preset = "audio_mp3".to_string();
+        config.preferences.video_resolution = "720p".to_string();
+        config.preferences.embed_metadata = true;
+        config.preferences.embed_thumbnail = false;
+        config.general.download_path = Some("/media".to_string());
+        config.general.audio_download_path = Some("/media/music".to_string());
+        config.general.filename_template = "%(uploader)s - %(title)s.%(ext)s".to_string();
+        config
+    }
+
+    #[test]
+    fn omitted_options_resolve_to_preferences() {
+        let resolved = resolve_defaults(&StartDownloadOptions::default(), &configured());
+        assert_eq!(resolved, ResolvedDefaults {
+            format_preset: DownloadFormatPreset::AudioMp3,
+            download_path: Some("/media/music".to_string()),
+            video_resolution: "720p".to_string(),
+            embed_metadata: true,
+            embed_thumbnail: false,
+            filename_template: "%(uploader)s - %(title)s.%(ext)s".to_string(),
+        });
+    }
+
+    #[test]
+    fn explicit_options_override_preferences() {
+        let options = StartDownloadOptions {
+            format_preset: Some(DownloadFormatPreset::Best),
+            download_path: Some("/tmp/dl".to_string()),
+            video_resolution: Some("1080p".to_string()),
+            embed_metadata: Some(false),
+            embed_thumbnail: Some(true),
+            filename_template: Some("%(id)s.%(ext)s".to_string()),
+            ..Default::default()
+        };
+        let resolved = resolve_defaults(&options, &configured());
+        assert_eq!(resolved, ResolvedDefaults {
+            format_preset: DownloadFormatPreset::Best,
+            download_path: Some("/tmp/dl".to_string()),
+            video_resolution: "1080p".to_string(),
+            embed_metadata: false,
+            embed_thumbnail: true,
+            filename_template: "%(id)s.%(ext)s".to_string(),
+        });
+    }
+
+    #[test]
+    fn download_path_falls_back_by_media_type() {
+        let mut config = configured();
+        let video = StartDownloadOptions { format_preset: Some(DownloadFormatPreset::Best), ..Default::default() };
+        // No video-specific folder: the global one applies
+        assert_eq!(resolve_defaults(&video, &config).download_path.as_deref(), Some("/media"));
+        config.general.video_download_path = Some("  ".to_string());
+        assert_eq!(resolve_defaults(&video, &config).download_path.as_deref(), Some("/media"));
+        config.general.video_download_path = Some("/media/video".to_string());
+        assert_eq!(resolve_defaults(&video, &config).download_path.as_deref(), Some("/media/video"));
+        config.general.download_path = None;
+        config.general.audio_download_path = None;
+        assert_eq!(resolve_defaults(&StartDownloadOptions::default(), &config).download_path, None);
+    }
+
+    #[test]
+    fn unknown_preset_preference_falls_back_to_best() {
+        let mut config = configured();
+        config.preferences.format_preset = "no_such_preset".to_string();
+        assert_eq!(resolve_defaults(&StartDownloadOptions::default(), &config).format_preset, DownloadFormatPreset::Best);
+    }
+
+    #[test]
+    fn music_library_forces_embedding_and_template() {
+        let options = StartDownloadOptions {
+            music_library: Some(true),
+            embed_metadata: Some(false),
+            embed_thumbnail: Some(false),
+            filename_template: Some("%(id)s.%(ext)s".to_string()),
+            ..Default::default()
+        };
+        let resolved = resolve_defaults(&options, &configured());
+        assert!(resolved.embed_metadata && resolved.embed_thumbnail);
+        assert_eq!(resolved.filename_template, MUSIC_LIBRARY_TEMPLATE);
+    }
+
+
+    #[test]
+    fn validation_accepts_any_http_host() {
+        assert!(is_supported_url("http://localhost:8080/video.mp4"));
+        assert!(is_supported_url("http://nas:8080/share/clip.mkv"));
+        assert!(is_supported_url("https://www.youtube.com/watch?v=abc"));
+        assert!(!is_supported_url("ftp://example.com/file"));
+        assert!(!is_supported_url("youtube.com/watch?v=abc"));
+    }
+
+    #[test]
+    fn clipboard_suggestions_need_a_dotted_host() {
+        assert!(looks_like_download_url("https://www.youtube.com/watch?v=abc"));
+        assert!(looks_like_download_url("https://user@example.com/video"));
+        assert!(!looks_like_download_url("http://localhost:8080/video.mp4"));
+        assert!(!looks_like_download_url("https://example.com/a video"));
+        assert!(!looks_like_download_url("https://.example/"));
+        assert!(!looks_like_download_url("just some text"));
+    }
+}
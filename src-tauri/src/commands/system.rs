@@ -1,9 +1,27 @@
 use std::process::Command;
-use tauri::{AppHandle, Manager};
+use tauri::{AppHandle, Manager, State};
 use serde::Serialize;
 use regex::Regex;
+use crate::commands::config::strip_secrets;
+use crate::config::ConfigManager;
 use crate::core::deps;
-use std::path::PathBuf;
+use crate::core::logging::{app_log_tail, LogManager};
+use crate::core::manager::JobManagerHandle;
+use crate::core::process::new_ytdlp_command;
+use crate::models::ActorStateDump;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use once_cell::sync::Lazy;
+use tokio::sync::Notify;
+
+/// Extractor names from `yt-dlp --list-extractors`, fetched once per session
+/// (or per yt-dlp install, see `invalidate_supported_sites`).
+static SUPPORTED_SITES: Lazy<Mutex<Option<Vec<String>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Lines of the app log included in a debug bundle.
+const DEBUG_BUNDLE_LOG_LINES: usize = 1000;
 
 #[derive(Serialize, Clone)]
 pub struct DependencyInfo {
@@ -13,11 +31,21 @@ pub struct DependencyInfo {
     pub path: Option<String>,
 }
 
+#[derive(Serialize)]
+pub struct StorageUsage {
+    pub temp_bytes: u64,
+    pub logs_bytes: u64,
+    pub bin_bytes: u64,
+    pub history_bytes: u64,
+}
+
 #[derive(Serialize)]
 pub struct AppDependencies {
     pub yt_dlp: DependencyInfo,
     pub ffmpeg: DependencyInfo,
     pub js_runtime: DependencyInfo,
+    /// The JS runtime was found and evaluated a trivial script
+    pub js_runtime_working: bool,
 }
 
 // Helper to create a command that doesn't spawn a visible window on Windows
@@ -99,6 +127,19 @@ pub fn get_js_runtime_info(bin_path: &PathBuf) -> Option<(String, String)> {
     None
 }
 
+/// Runs `1+1` through the runtime; a runtime that prints a version but can't evaluate
+/// code (broken install, missing libs) still leaves YouTube downloads throttled.
+fn js_runtime_smoke_test(name: &str, path: &str) -> bool {
+    let mut cmd = new_silent_command(path);
+    match name {
+        "deno" => cmd.arg("eval").arg("console.log(1+1)"),
+        _ => cmd.arg("-e").arg("console.log(1+1)"),
+    };
+    cmd.output()
+        .map(|o| o.status.success() && String::from_utf8_lossy(&o.stdout).trim() == "2")
+        .unwrap_or(false)
+}
+
 #[tauri::command]
 pub async fn check_dependencies(app_handle: AppHandle) -> AppDependencies {
     let app_dir = app_handle.path_resolver().app_data_dir().unwrap();
@@ -155,19 +196,92 @@ pub async fn check_dependencies(app_handle: AppHandle) -> AppDependencies {
              }
         }
 
+        let js_runtime_working = js_runtime.available
+            && js_runtime.path.as_deref().is_some_and(|p| js_runtime_smoke_test(&js_runtime.name, p));
+
         AppDependencies {
             yt_dlp,
             ffmpeg,
             js_runtime,
+            js_runtime_working,
         }
     })
     .await
     .unwrap()
 }
 
+/// Fails fast with a clear message instead of letting a network call time out in offline mode.
+fn ensure_online(app_handle: &AppHandle) -> Result<(), String> {
+    if app_handle.state::<Arc<ConfigManager>>().get_config().general.offline_mode {
+        return Err("Offline mode is on; network features are disabled. Turn it off in Settings to use this.".to_string());
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn install_dependency(app_handle: AppHandle, name: String) -> Result<(), String> {
-    deps::install_dep(name, app_handle).await
+    ensure_online(&app_handle)?;
+    deps::install_dep(name, app_handle.clone()).await?;
+    // A first-run install of yt-dlp is what startup work (auto-resume) has been waiting for
+    signal_dependencies_ready(&check_dependencies(app_handle).await);
+    Ok(())
+}
+
+/// Signalled once yt-dlp is available (after startup sync or a later install), so work that
+/// needs it can begin.
+pub static DEPENDENCIES_READY: Lazy<Notify> = Lazy::new(Notify::new);
+
+fn signal_dependencies_ready(deps: &AppDependencies) {
+    if deps.yt_dlp.available {
+        // notify_one stores a permit, so a waiter that subscribes later still wakes
+        DEPENDENCIES_READY.notify_one();
+    } else {
+        tracing::info!("yt-dlp is missing; startup work waits until it is installed");
+    }
+}
+
+/// How long startup waits for update checks before continuing with local binaries.
+const DEPENDENCY_SYNC_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Serialize, Clone)]
+pub struct DependencySyncResult {
+    pub name: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+fn spawn_sync_task<F>(app_handle: AppHandle, name: &'static str, task: F) -> tauri::async_runtime::JoinHandle<()>
+where
+    F: std::future::Future<Output = Result<(), String>> + Send + 'static,
+{
+    tauri::async_runtime::spawn(async move {
+        let result = task.await;
+        if let Err(e) = &result {
+            tracing::warn!("Dependency sync for {} failed: {}", name, e);
+        }
+        let _ = app_handle.emit_all("dependency-sync-result", DependencySyncResult {
+            name: name.to_string(),
+            success: result.is_ok(),
+            error: result.err(),
+        });
+    })
+}
+
+/// Re-applies execute permissions to the managed binaries (Unix); returns the files checked.
+#[tauri::command]
+pub fn repair_bin_permissions(app_handle: AppHandle) -> Result<Vec<String>, String> {
+    let app_dir = app_handle.path_resolver().app_data_dir().ok_or("Failed to get app dir")?;
+    deps::repair_bin_permissions(&app_dir.join("bin"))
+}
+
+/// Aborts an in-flight dependency download ("yt-dlp", "ffmpeg" or "js_runtime").
+#[tauri::command]
+pub fn cancel_dependency_install(name: String) -> Result<(), String> {
+    if deps::cancel_install(&name) {
+        Ok(())
+    } else {
+        Err(format!("No installation of '{}' is in progress", name))
+    }
 }
 
 #[tauri::command]
@@ -179,11 +293,37 @@ pub async fn sync_dependencies(app_handle: AppHandle) -> Result<AppDependencies,
         std::fs::create_dir_all(&bin_dir).map_err(|e| e.to_string())?;
     }
 
-    deps::auto_update_yt_dlp(app_handle.clone(), bin_dir.clone()).await?;
-    deps::install_missing_ffmpeg(app_handle.clone(), bin_dir.clone()).await?;
-    deps::manage_js_runtime(app_handle.clone(), bin_dir.clone()).await?;
+    if let Err(e) = deps::repair_bin_permissions(&bin_dir) {
+        tracing::warn!("Failed to repair binary permissions: {}", e);
+    }
 
-    Ok(check_dependencies(app_handle).await)
+    // Only bound the wait when the app can already run; on first install there is nothing to fall back to
+    let local = check_dependencies(app_handle.clone()).await;
+    if ensure_online(&app_handle).is_err() {
+        tracing::info!("Offline mode: skipping dependency update checks");
+        signal_dependencies_ready(&local);
+        return Ok(local);
+    }
+    let can_skip = local.yt_dlp.available && local.ffmpeg.available;
+
+    // Spawned rather than joined directly so a timed-out update keeps running in the background
+    // instead of being dropped halfway through writing a binary.
+    let yt_dlp = spawn_sync_task(app_handle.clone(), "yt-dlp", deps::auto_update_yt_dlp(app_handle.clone(), bin_dir.clone()));
+    let ffmpeg = spawn_sync_task(app_handle.clone(), "ffmpeg", deps::install_missing_ffmpeg(app_handle.clone(), bin_dir.clone()));
+    let js_runtime = spawn_sync_task(app_handle.clone(), "js_runtime", deps::manage_js_runtime(app_handle.clone(), bin_dir.clone()));
+
+    let all = async { let _ = tokio::join!(yt_dlp, ffmpeg, js_runtime); };
+    if can_skip {
+        if tokio::time::timeout(DEPENDENCY_SYNC_TIMEOUT, all).await.is_err() {
+            tracing::warn!("Dependency sync timed out; continuing with local binaries");
+        }
+    } else {
+        all.await;
+    }
+
+    let deps = check_dependencies(app_handle).await;
+    signal_dependencies_ready(&deps);
+    Ok(deps)
 }
 
 #[tauri::command]
@@ -204,8 +344,61 @@ pub fn close_splash(app_handle: AppHandle) {
     }
 }
 
+/// Endpoints probed by `check_connectivity`: (label, url).
+const CONNECTIVITY_TARGETS: &[(&str, &str)] = &[
+    ("GitHub API", "https://api.github.com"),
+    ("YouTube", "https://www.youtube.com"),
+];
+
+const CONNECTIVITY_TIMEOUT: Duration = Duration::from_secs(8);
+
+#[derive(Serialize, Clone)]
+pub struct ConnectivityResult {
+    pub target: String,
+    pub url: String,
+    pub reachable: bool,
+    pub status: Option<u16>,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+async fn probe_target(client: &reqwest::Client, target: &str, url: &str) -> ConnectivityResult {
+    let started = std::time::Instant::now();
+    let result = tokio::time::timeout(CONNECTIVITY_TIMEOUT, client.head(url).send()).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let (reachable, status, latency_ms, error) = match result {
+        // Any HTTP response, even an error status, means the host answered.
+        Ok(Ok(resp)) => (true, Some(resp.status().as_u16()), Some(latency_ms), None),
+        Ok(Err(e)) => (false, None, None, Some(e.to_string())),
+        Err(_) => (false, None, None, Some(format!("Timed out after {}s", CONNECTIVITY_TIMEOUT.as_secs()))),
+    };
+
+    ConnectivityResult {
+        target: target.to_string(),
+        url: url.to_string(),
+        reachable,
+        status,
+        latency_ms,
+        error,
+    }
+}
+
+/// Checks whether the app can reach GitHub and a video host, using the same
+/// HTTP client (and proxy environment) as dependency downloads.
+#[tauri::command]
+pub async fn check_connectivity(app_handle: AppHandle) -> Result<Vec<ConnectivityResult>, String> {
+    ensure_online(&app_handle)?;
+    let client = deps::get_http_client()?;
+    let probes = CONNECTIVITY_TARGETS
+        .iter()
+        .map(|(target, url)| probe_target(&client, target, url));
+    Ok(futures_util::future::join_all(probes).await)
+}
+
 #[tauri::command]
-pub async fn get_latest_app_version() -> Result<String, String> {
+pub async fn get_latest_app_version(app_handle: AppHandle) -> Result<String, String> {
+    ensure_online(&app_handle)?;
     deps::get_latest_github_tag("zqily/multiyt-dlp").await
 }
 
@@ -259,4 +452,224 @@ pub fn show_in_folder(path: String) -> Result<(), String> {
             Err("Could not determine parent directory".to_string())
         }
     }
-}
\ No newline at end of file
+}
+
+/// Opens a file or folder with the OS default handler (editor for config.json, file manager for dirs).
+fn open_with_default_app(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("Not found: {}", path.display()));
+    }
+    #[cfg(target_os = "windows")]
+    let program = "explorer";
+    #[cfg(target_os = "macos")]
+    let program = "open";
+    #[cfg(target_os = "linux")]
+    let program = "xdg-open";
+
+    Command::new(program)
+        .arg(path)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+}
+
+/// Opens `~/.multiyt-dlp/config.json` for hand-editing; the config watcher picks up the save.
+#[tauri::command]
+pub fn open_config_file(config_manager: State<'_, Arc<ConfigManager>>) -> Result<(), String> {
+    open_with_default_app(config_manager.file_path())
+}
+
+/// Opens the `~/.multiyt-dlp` data folder (config, logs, persisted queue, caches).
+#[tauri::command]
+pub fn open_data_folder() -> Result<(), String> {
+    let home = dirs::home_dir().ok_or("Could not find home directory")?;
+    open_with_default_app(&home.join(".multiyt-dlp"))
+}
+
+/// Internal job manager counters and job map. Only available with `enable_debug_tools`.
+#[tauri::command]
+pub async fn debug_dump_state(
+    config_manager: State<'_, Arc<ConfigManager>>,
+    manager: State<'_, JobManagerHandle>,
+) -> Result<ActorStateDump, String> {
+    if !config_manager.get_config().general.enable_debug_tools {
+        return Err("Debug tools are disabled; set enable_debug_tools in the config to use this.".to_string());
+    }
+    manager.debug_dump().await
+}
+
+/// Zips the recent app log, the config with secrets stripped, dependency versions and OS info
+/// into `multiyt-dlp-debug-<timestamp>.zip` in the Downloads folder, reveals it and returns its path.
+#[tauri::command]
+pub async fn create_debug_bundle(
+    app_handle: AppHandle,
+    config_manager: State<'_, Arc<ConfigManager>>,
+) -> Result<String, String> {
+    let mut config = config_manager.get_config();
+    strip_secrets(&mut config);
+    let config_json = serde_json::to_string_pretty(&config).map_err(|e| format!("Serialization error: {}", e))?;
+    let deps_json = serde_json::to_string_pretty(&check_dependencies(app_handle).await)
+        .map_err(|e| format!("Serialization error: {}", e))?;
+
+    let home = dirs::home_dir().ok_or("Could not find home directory")?;
+    let log_tail = app_log_tail(&home.join(".multiyt-dlp").join("logs"), DEBUG_BUNDLE_LOG_LINES)?;
+    let now = chrono::Local::now();
+    let system_info = format!(
+        "Multiyt-dlp {}\nTime: {}\nOS: {} {} ({})\n",
+        env!("CARGO_PKG_VERSION"),
+        now.to_rfc3339(),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        std::env::consts::FAMILY,
+    );
+
+    let out_dir = dirs::download_dir().unwrap_or(home);
+    let bundle_path = out_dir.join(format!("multiyt-dlp-debug-{}.zip", now.format("%Y%m%d-%H%M%S")));
+    let files = [
+        ("system.txt", system_info),
+        ("config.json", config_json),
+        ("dependencies.json", deps_json),
+        ("app.log", log_tail),
+    ];
+
+    let path = bundle_path.clone();
+    tauri::async_runtime::spawn_blocking(move || -> Result<(), String> {
+        let file = std::fs::File::create(&path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let mut zip = zip::ZipWriter::new(file);
+        for (name, contents) in files {
+            zip.start_file(name, zip::write::FileOptions::default()).map_err(|e| e.to_string())?;
+            zip.write_all(contents.as_bytes()).map_err(|e| e.to_string())?;
+        }
+        zip.finish().map_err(|e| e.to_string())?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let bundle_path = bundle_path.to_string_lossy().to_string();
+    tracing::info!("Wrote debug bundle to {}", bundle_path);
+    if let Err(e) = show_in_folder(bundle_path.clone()) {
+        tracing::warn!("Could not reveal debug bundle: {}", e);
+    }
+    Ok(bundle_path)
+}
+
+#[tauri::command]
+pub fn get_error_log(log_manager: State<'_, LogManager>, max_lines: Option<usize>) -> Result<String, String> {
+    log_manager.read_error_log(max_lines.unwrap_or(500))
+}
+
+/// Free bytes available to this user on the volume holding `path`. A folder that doesn't
+/// exist yet is measured at its nearest existing ancestor. `None` if the query fails.
+pub fn available_space(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|p| p.exists())?;
+    disk_free_bytes(existing)
+}
+
+#[cfg(target_os = "windows")]
+fn disk_free_bytes(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut free: u64 = 0;
+    unsafe { GetDiskFreeSpaceExW(PCWSTR(wide.as_ptr()), Some(&mut free as *mut u64), None, None) }.ok().ok()?;
+    Some(free)
+}
+
+#[cfg(not(target_os = "windows"))]
+#[allow(clippy::useless_conversion)] // statvfs field widths differ between Unix targets
+fn disk_free_bytes(path: &Path) -> Option<u64> {
+    let stat = nix::sys::statvfs::statvfs(path).ok()?;
+    Some(u64::from(stat.blocks_available()) * u64::from(stat.fragment_size()))
+}
+
+/// Creates `path` if needed and checks a file can actually be written there
+/// (catches read-only mounts and permission issues that `exists()` does not).
+pub fn ensure_writable_dir(path: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(path)
+        .map_err(|e| format!("Cannot create folder {}: {}", path.display(), e))?;
+    let probe = path.join(format!(".multiyt-write-test-{}", std::process::id()));
+    std::fs::write(&probe, b"")
+        .map_err(|e| format!("Folder {} is not writable: {}", path.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Total size of all files under `path`. Symlinks are not followed; unreadable entries count as 0.
+pub fn dir_size(path: &Path) -> u64 {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    entries.flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) if meta.is_file() => meta.len(),
+            _ => 0,
+        })
+        .sum()
+}
+
+#[tauri::command]
+pub async fn get_storage_usage(app_handle: AppHandle, log_manager: State<'_, LogManager>) -> Result<StorageUsage, String> {
+    let app_dir = app_handle.path_resolver().app_data_dir().ok_or("Failed to get app dir")?;
+    let home = dirs::home_dir().ok_or("Could not find home directory")?;
+    let log_dir = log_manager.log_dir().to_path_buf();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let data_dir = home.join(".multiyt-dlp");
+        StorageUsage {
+            temp_bytes: dir_size(&data_dir.join("temp_downloads")),
+            logs_bytes: dir_size(&log_dir),
+            bin_bytes: dir_size(&app_dir.join("bin")),
+            history_bytes: std::fs::metadata(data_dir.join("jobs.json")).map(|m| m.len()).unwrap_or(0),
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn clear_temp(manager: State<'_, JobManagerHandle>) -> Result<u64, String> {
+    manager.clear_temp().await
+}
+
+#[tauri::command]
+pub fn clear_logs(log_manager: State<'_, LogManager>) -> Result<u64, String> {
+    log_manager.clear_old_logs()
+}
+
+/// Drops the cached extractor list; called after yt-dlp is installed or updated.
+pub fn invalidate_supported_sites() {
+    *SUPPORTED_SITES.lock().unwrap() = None;
+}
+
+#[tauri::command]
+pub async fn get_supported_sites(app_handle: AppHandle) -> Result<Vec<String>, String> {
+    if let Some(sites) = SUPPORTED_SITES.lock().unwrap().clone() {
+        return Ok(sites);
+    }
+
+    let app_dir = app_handle.path_resolver().app_data_dir().ok_or("Failed to get app dir")?;
+    let output = new_ytdlp_command(&app_dir.join("bin"))
+        .arg("--list-extractors")
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run yt-dlp: {}", e))?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+
+    let sites: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.contains("(CURRENTLY BROKEN)"))
+        .map(|l| l.to_string())
+        .collect();
+
+    *SUPPORTED_SITES.lock().unwrap() = Some(sites.clone());
+    Ok(sites)
+}
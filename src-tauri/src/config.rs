@@ -3,6 +3,7 @@ use serde_json::Value;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Mutex;
+use std::time::SystemTime;
 
 // --- Configuration Structs ---
 
@@ -26,33 +27,244 @@ impl Default for WindowConfig {
     }
 }
 
+/// A daily time window (local time) with its own download rate cap.
+/// `start_hour` is inclusive, `end_hour` exclusive; windows may wrap past midnight (22 -> 6).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RateWindow {
+    pub start_hour: u32,
+    pub end_hour: u32,
+    /// yt-dlp rate such as "1M"; None or empty means unlimited
+    pub limit: Option<String>,
+}
+
+impl RateWindow {
+    pub fn contains(&self, hour: u32) -> bool {
+        if self.start_hour <= self.end_hour {
+            hour >= self.start_hour && hour < self.end_hour
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
+/// Units for displayed download speeds.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SpeedUnits {
+    /// Base 1024: KiB/s, MiB/s (yt-dlp's own convention)
+    #[default]
+    Binary,
+    /// Base 1000: KB/s, MB/s
+    Decimal,
+}
+
+/// How HDR formats are ranked when a video has both HDR and SDR versions.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HdrPreference {
+    /// yt-dlp's default ranking (HDR usually wins at the top resolutions)
+    #[default]
+    Keep,
+    /// Rank SDR first; avoids washed-out colors on SDR screens
+    PreferSdr,
+    /// Rank the best dynamic range first (Dolby Vision > HDR10+ > HDR10 > HLG > SDR)
+    PreferHdr,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(default)]
 pub struct GeneralConfig {
     pub download_path: Option<String>,
+    // Per-type default folders; take precedence over download_path when set
+    pub audio_download_path: Option<String>,
+    pub video_download_path: Option<String>,
+    // Free space (e.g. "5G") the target drive must keep; below it the queue pauses until space frees up
+    pub min_free_space: Option<String>,
+    // Spill-over folders (e.g. other drives), tried in order when a job's folder is low on space
+    pub download_path_pool: Vec<String>,
+    // Free space a folder must keep to receive new jobs when a pool is set, e.g. "10G"
+    pub download_path_pool_min_free: String,
     pub filename_template: String,
     pub template_blocks_json: Option<String>,
+    // strftime format applied to %(upload_date)s in templates (e.g. "%Y-%m-%d"); raw YYYYMMDD when unset
+    pub upload_date_format: Option<String>,
+    // strftime subfolder (e.g. "%Y/%m") added under the target folder, using the download date
+    pub date_subfolder_format: Option<String>,
     pub max_concurrent_downloads: u32,
     pub max_total_instances: u32,
+    // Downloads allowed at once from the same host; 0 means only the global limit applies
+    pub max_concurrent_per_host: u32,
+    // yt-dlp processes allowed at once for playlist expansion / URL probing
+    pub max_concurrent_probes: u32,
+    // Which tabs a bare YouTube channel URL expands to (/videos, /shorts, /streams)
+    pub channel_include_videos: bool,
+    pub channel_include_shorts: bool,
+    pub channel_include_streams: bool,
     pub log_level: String, 
     pub check_for_updates: bool,
+    // No network for dependency checks/installs or app update checks; only binaries already in bin/ are used
+    pub offline_mode: bool,
     // NEW: Cookies
     pub cookies_path: Option<String>,
     pub cookies_from_browser: Option<String>, // "chrome", "firefox", etc. or None
+    // Linux keyring holding the Chromium cookie key ("gnomekeyring", "kwallet6", ...), passed as browser+keyring
+    pub cookies_browser_keyring: Option<String>,
+    // Pasted Netscape cookie jar, written to a temp file per run (used when no cookies file is set)
+    pub cookies_text: Option<String>,
+    // Export cookies_from_browser to a fresh file before each job instead of per-process reads,
+    // so long queues don't run on cookies the site has since rotated
+    pub refresh_browser_cookies: bool,
+    // Hard resolution cap applied to every job, regardless of preset
+    pub global_max_height: Option<u32>,
+    // yt-dlp -S sort string (e.g. "res,fps,vcodec:h264"); orders formats within the preset's selection
+    pub format_sort: Option<String>,
+    // Pick single-file (pre-merged) video formats: skips ffmpeg merging at the cost of quality,
+    // since sites often only offer progressive streams up to 720p
+    pub prefer_progressive: bool,
+    // EBU R128 loudness normalization (ffmpeg loudnorm) during audio conversion. Adds a filter
+    // pass to extraction; only MP3/FLAC, since Best/M4A may stream-copy and can't be filtered
+    pub normalize_audio: bool,
+    // --sub-langs for the subtitles-only preset (comma-separated, regex allowed, e.g. "en.*,de")
+    pub subtitle_langs: String,
+    // HDR vs SDR ranking, applied as a -S field ahead of format_sort
+    pub hdr_preference: HdrPreference,
+    // Base-1024 (MiB/s) or base-1000 (MB/s) for speeds shown in progress
+    pub speed_units: SpeedUnits,
+    // Ignore leftover/partial files and fetch a fresh copy (--no-continue --force-overwrites)
+    pub redownload_existing: bool,
+    // When the job starts, predict the output filename and skip the job if that file is already in
+    // the target folder (works without an archive and for files from elsewhere). Runs after the job
+    // took its download slot, since the target folder (date subfolder, path pool) is only resolved
+    // then; the extra probe briefly occupies that slot
+    pub skip_existing: bool,
+    // --convert-thumbnails target for embedded thumbnails ("jpg", "png" or "webp"); when unset,
+    // MP4/MKV output gets jpg since some ffmpeg builds can't embed WebP into them
+    pub thumbnail_format: Option<String>,
+    // Resume jobs left over from the last session once dependencies are ready, without asking
+    pub auto_resume_on_startup: bool,
+    // Keep yt-dlp's .info.json sidecar next to each file (feeds the details view)
+    pub write_info_json: bool,
+    // Local HTTP API for remote control (see core::http_api); read at startup, off by default
+    pub enable_http_api: bool,
+    pub http_api_bind: String,
+    // Bearer token required on every API request
+    pub http_api_token: Option<String>,
+    // Global yt-dlp --proxy (http(s)/socks4/socks5, credentials allowed); jobs may override it
+    pub proxy: Option<String>,
+    // User-maintained yt-dlp config file, loaded via --config-location
+    pub ytdlp_config_path: Option<String>,
+    // Custom ffmpeg binary, or the folder holding it, passed as --ffmpeg-location; the managed/PATH one when unset
+    pub ffmpeg_path: Option<String>,
+    // Download rate cap (e.g. "2M"), overridden by a matching `rate_schedule` window
+    pub rate_limit: Option<String>,
+    pub rate_schedule: Vec<RateWindow>,
+    // --fragment-retries for HLS/DASH downloads; higher rides out flaky (mobile) connections
+    pub fragment_retries: u32,
+    // --buffer-size (e.g. "16K"); yt-dlp's default when unset
+    pub buffer_size: Option<String>,
+    // -N: fragments of one HLS/DASH download fetched in parallel (1 = sequential); no effect on progressive files
+    pub concurrent_fragments: u32,
+    // --http-chunk-size (e.g. "10M"): fetch progressive files in ranged chunks, which gets
+    // around per-connection throttling on some CDNs (YouTube caps unchunked streams); off when unset
+    pub http_chunk_size: Option<String>,
+    // Probe finished files with ffprobe and retry once if they look corrupt
+    pub verify_output: bool,
+    // Fail a download whose final file is well below the size yt-dlp reported (likely truncated)
+    pub verify_size: bool,
+    // Fail YouTube downloads up front when no JS runtime is installed, instead of only warning
+    pub require_js_runtime: bool,
+    // Retry with --restrict-filenames after a filesystem error instead of failing outright
+    pub auto_sanitize_retry: bool,
+    // Command run after each successful download; supports {path}, {title} and {url}
+    pub post_hook_command: Option<String>,
+    // Delete the downloaded file after the post hook exits successfully (e.g. it uploaded the file)
+    pub delete_after_hook: bool,
+    // Pause the queue after the first failed job instead of continuing
+    pub stop_queue_on_error: bool,
+    // Remember pause_all across restarts (the queue stays paused until resume_all)
+    pub persist_global_pause: bool,
+    // Refuse to queue a URL that is already pending or downloading
+    pub reject_duplicate_urls: bool,
+    // Exposes troubleshooting commands such as debug_dump_state
+    pub enable_debug_tools: bool,
+    // Save a crash-<timestamp>.log (panic, backtrace, recent log) to the data folder on a panic
+    pub write_crash_reports: bool,
+}
+
+impl GeneralConfig {
+    /// Rate limit in effect at the given local hour, if any.
+    pub fn effective_rate_limit(&self, hour: u32) -> Option<String> {
+        let limit = match self.rate_schedule.iter().find(|w| w.contains(hour)) {
+            Some(window) => window.limit.clone(),
+            None => self.rate_limit.clone(),
+        };
+        limit.filter(|l| !l.trim().is_empty())
+    }
 }
 
 impl Default for GeneralConfig {
     fn default() -> Self {
         Self {
             download_path: None, 
+            audio_download_path: None,
+            video_download_path: None,
+            min_free_space: None,
+            download_path_pool: Vec::new(),
+            download_path_pool_min_free: "10G".to_string(),
             filename_template: "%(title)s.%(ext)s".to_string(),
             template_blocks_json: None,
+            upload_date_format: None,
+            date_subfolder_format: None,
             max_concurrent_downloads: 4,
+            max_concurrent_per_host: 0,
+            max_concurrent_probes: 2,
+            channel_include_videos: true,
+            channel_include_shorts: true,
+            channel_include_streams: true,
             max_total_instances: 10,
             log_level: "info".to_string(),
             check_for_updates: true,
+            offline_mode: false,
             cookies_path: None,
             cookies_from_browser: None,
+            cookies_browser_keyring: None,
+            cookies_text: None,
+            refresh_browser_cookies: false,
+            global_max_height: None,
+            format_sort: None,
+            prefer_progressive: false,
+            normalize_audio: false,
+            subtitle_langs: "en.*".to_string(),
+            hdr_preference: HdrPreference::Keep,
+            speed_units: SpeedUnits::Binary,
+            redownload_existing: false,
+            skip_existing: false,
+            thumbnail_format: None,
+            auto_resume_on_startup: false,
+            write_info_json: false,
+            enable_http_api: false,
+            http_api_bind: "127.0.0.1:8765".to_string(),
+            http_api_token: None,
+            proxy: None,
+            ytdlp_config_path: None,
+            ffmpeg_path: None,
+            rate_limit: None,
+            fragment_retries: 10,
+            buffer_size: None,
+            concurrent_fragments: 1,
+            http_chunk_size: None,
+            rate_schedule: Vec::new(),
+            verify_output: false,
+            verify_size: false,
+            require_js_runtime: false,
+            auto_sanitize_retry: true,
+            post_hook_command: None,
+            delete_after_hook: false,
+            stop_queue_on_error: false,
+            persist_global_pause: false,
+            enable_debug_tools: false,
+            write_crash_reports: false,
+            reject_duplicate_urls: true,
         }
     }
 }
@@ -106,6 +318,8 @@ impl Default for AppConfig {
 pub struct ConfigManager {
     config: Mutex<AppConfig>,
     file_path: PathBuf,
+    // mtime of our own last write, so the watcher only reacts to outside edits
+    last_written: Mutex<Option<SystemTime>>,
 }
 
 impl ConfigManager {
@@ -125,6 +339,7 @@ impl ConfigManager {
         let manager = Self {
             config: Mutex::new(config),
             file_path,
+            last_written: Mutex::new(None),
         };
         let _ = manager.save();
         
@@ -215,7 +430,62 @@ impl ConfigManager {
         };
         
         fs::write(&self.file_path, json)
-            .map_err(|e| format!("Failed to write config file: {}", e))
+            .map_err(|e| format!("Failed to write config file: {}", e))?;
+        *self.last_written.lock().unwrap() = self.file_modified();
+        Ok(())
+    }
+
+    pub fn file_path(&self) -> &PathBuf {
+        &self.file_path
+    }
+
+    fn file_modified(&self) -> Option<SystemTime> {
+        fs::metadata(&self.file_path).and_then(|m| m.modified()).ok()
+    }
+
+    /// Reloads the file if something other than `save` changed it, returning the new config.
+    /// Half-finished edits that aren't valid JSON are left alone rather than "repaired" away,
+    /// and so are edits `validate` rejects: the current config stays in effect.
+    pub fn reload_if_changed(&self, validate: impl FnOnce(&AppConfig) -> Result<(), String>) -> Option<AppConfig> {
+        let modified = self.file_modified()?;
+        {
+            let mut last = self.last_written.lock().unwrap();
+            if *last == Some(modified) {
+                return None;
+            }
+            *last = Some(modified);
+        }
+
+        let content = fs::read_to_string(&self.file_path).ok()?;
+        if let Err(e) = serde_json::from_str::<Value>(&content) {
+            tracing::warn!("Ignoring external config edit, not valid JSON yet: {}", e);
+            return None;
+        }
+        let config = Self::load_robustly(&self.file_path);
+        if let Err(e) = validate(&config) {
+            tracing::warn!("Ignoring external config edit, keeping the current settings: {}", e);
+            return None;
+        }
+        self.replace(config.clone());
+        Some(config)
+    }
+
+    /// Tolerantly merges a (possibly partial) JSON settings object over the current
+    /// config, using the same rules as the on-disk repair path. Does not apply the result.
+    pub fn merge_over_current(&self, overlay: &Value) -> Result<AppConfig, String> {
+        if !overlay.is_object() {
+            return Err("Settings must be a JSON object".to_string());
+        }
+        let mut current_json = serde_json::to_value(self.get_config())
+            .map_err(|e| format!("Serialization error: {}", e))?;
+        Self::tolerant_merge(&mut current_json, overlay);
+        serde_json::from_value(current_json)
+            .map_err(|e| format!("Invalid settings: {}", e))
+    }
+
+    pub fn replace(&self, config: AppConfig) {
+        let mut cfg = self.config.lock().unwrap();
+        *cfg = config;
     }
 
     pub fn get_config(&self) -> AppConfig {
@@ -232,8 +502,76 @@ impl ConfigManager {
         cfg.preferences = prefs;
     }
 
-    pub fn update_window(&self, window: WindowConfig) {
+    /// Mutates the window geometry in place; called on every move/resize event, so avoids
+    /// cloning the whole config.
+    pub fn update_window(&self, apply: impl FnOnce(&mut WindowConfig)) {
         let mut cfg = self.config.lock().unwrap();
-        cfg.window = window;
+        apply(&mut cfg.window);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(start_hour: u32, end_hour: u32, limit: &str) -> RateWindow {
+        RateWindow { start_hour, end_hour, limit: Some(limit.to_string()) }
+    }
+
+    #[test]
+    fn window_end_hour_is_exclusive() {
+        let day = window(9, 17, "1M");
+        assert!(day.contains(9));
+        assert!(day.contains(16));
+        assert!(!day.contains(17));
+        assert!(!day.contains(8));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn window_wraps_midnight() {
+        let night = window(22, 6, "1M");
+        assert!(night.contains(22));
+        assert!(night.contains(23));
+        assert!(night.contains(0));
+        assert!(night.contains(5));
+        assert!(!night.contains(6));
+        assert!(!night.contains(12));
+    }
+
+    #[test]
+    fn effective_rate_limit_prefers_matching_window() {
+        let config = GeneralConfig {
+            rate_limit: Some("5M".into()),
+            rate_schedule: vec![window(22, 6, "500K"), RateWindow { start_hour: 12, end_hour: 13, limit: None }],
+            ..Default::default()
+        };
+        assert_eq!(config.effective_rate_limit(23).as_deref(), Some("500K"));
+        assert_eq!(config.effective_rate_limit(3).as_deref(), Some("500K"));
+        assert_eq!(config.effective_rate_limit(6).as_deref(), Some("5M"));
+        // A window without a limit means unlimited, not the global cap
+        assert_eq!(config.effective_rate_limit(12), None);
+    }
+
+
+    #[test]
+    fn rejected_external_edit_keeps_current_config() {
+        let dir = std::env::temp_dir().join(format!("multiyt-dlp-config-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let manager = ConfigManager {
+            config: Mutex::new(AppConfig::default()),
+            file_path: dir.join("config.json"),
+            last_written: Mutex::new(None),
+        };
+        let mut edited = AppConfig::default();
+        edited.general.filename_template = "%(id)s.%(ext)s".to_string();
+        fs::write(&manager.file_path, serde_json::to_string(&edited).unwrap()).unwrap();
+
+        assert!(manager.reload_if_changed(|_| Err("invalid".to_string())).is_none());
+        assert_eq!(manager.get_config().general.filename_template, AppConfig::default().general.filename_template);
+
+        *manager.last_written.lock().unwrap() = None;
+        let reloaded = manager.reload_if_changed(|_| Ok(())).unwrap();
+        assert_eq!(reloaded.general.filename_template, "%(id)s.%(ext)s");
+        assert_eq!(manager.get_config().general.filename_template, "%(id)s.%(ext)s");
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,83 @@
+use std::backtrace::Backtrace;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::core::logging::app_log_tail;
+
+/// Mirrors `write_crash_reports`; read from the hook, which must not touch the config mutex
+/// (the panicking thread may be holding it).
+static REPORTS_ENABLED: AtomicBool = AtomicBool::new(false);
+/// Set once the app is up, so a panic can be surfaced to the UI.
+static APP_HANDLE: OnceCell<AppHandle> = OnceCell::new();
+
+/// Lines of the app log included in a crash report.
+const LOG_TAIL_LINES: usize = 200;
+
+#[derive(Clone, Serialize)]
+pub struct AppPanicPayload {
+    pub message: String,
+    /// Crash report written for this panic, if reports are enabled
+    #[serde(rename = "reportPath")]
+    pub report_path: Option<String>,
+}
+
+pub fn set_reports_enabled(enabled: bool) {
+    REPORTS_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn attach_app_handle(app_handle: AppHandle) {
+    let _ = APP_HANDLE.set(app_handle);
+}
+
+/// Logs every panic, optionally writes `~/.multiyt-dlp/crash-<timestamp>.log` (message,
+/// backtrace, recent log lines) and emits `app-panic`. The default hook still runs afterwards.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let reason = payload.downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        let location = info.location().map(|l| format!(" at {}:{}", l.file(), l.line())).unwrap_or_default();
+        let message = format!("{}{}", reason, location);
+        tracing::error!("Panic: {}", message);
+
+        let report_path = if REPORTS_ENABLED.load(Ordering::Relaxed) {
+            write_report(&message, &Backtrace::force_capture())
+        } else {
+            None
+        };
+        if let Some(app_handle) = APP_HANDLE.get() {
+            let _ = app_handle.emit_all("app-panic", AppPanicPayload {
+                message,
+                report_path: report_path.map(|p| p.to_string_lossy().to_string()),
+            });
+        }
+
+        default_hook(info);
+    }));
+}
+
+fn write_report(message: &str, backtrace: &Backtrace) -> Option<PathBuf> {
+    let data_dir = dirs::home_dir()?.join(".multiyt-dlp");
+    let now = chrono::Local::now();
+    let path = data_dir.join(format!("crash-{}.log", now.format("%Y%m%d-%H%M%S")));
+    let log_tail = app_log_tail(&data_dir.join("logs"), LOG_TAIL_LINES).unwrap_or_default();
+
+    let report = format!(
+        "Multiyt-dlp {} crash report\nTime: {}\nOS: {} {}\n\nPanic: {}\n\nBacktrace:\n{}\n\nRecent log:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        now.to_rfc3339(),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        message,
+        backtrace,
+        log_tail,
+    );
+    fs::write(&path, report).ok()?;
+    Some(path)
+}
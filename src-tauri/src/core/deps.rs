@@ -1,12 +1,20 @@
 use std::fs::{self, File};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Manager};
 use futures_util::StreamExt;
 use serde::Serialize;
 use reqwest::{Client, header};
 use std::process::Command;
+use std::time::Instant;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::ConfigManager;
+use crate::core::util::{format_eta, format_speed};
 
 // ... [Existing imports and constants remain unchanged] ...
 
@@ -38,18 +46,50 @@ struct InstallProgressPayload {
     name: String,
     percentage: u64,
     status: String,
+    // Only set while a file is downloading
+    speed: Option<String>,
+    eta: Option<String>,
 }
 
 #[async_trait]
 pub trait DependencyProvider: Send + Sync {
     fn get_name(&self) -> String;
     fn get_binaries(&self) -> Vec<&str>;
-    async fn install(&self, app_handle: AppHandle, target_dir: PathBuf) -> Result<(), String>;
+    async fn install(&self, app_handle: AppHandle, target_dir: PathBuf, cancel: CancellationToken) -> Result<(), String>;
+}
+
+/// Cancellation tokens of installs in progress, keyed by provider name.
+static ACTIVE_INSTALLS: Lazy<Mutex<HashMap<String, CancellationToken>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Clone, Serialize)]
+struct InstallCancelledPayload {
+    name: String,
+}
+
+/// Runs `provider.install` with a cancellation token registered under its name,
+/// so `cancel_install` can abort the download.
+async fn run_install(provider: &dyn DependencyProvider, app_handle: AppHandle, target_dir: PathBuf) -> Result<(), String> {
+    let name = provider.get_name();
+    let token = CancellationToken::new();
+    ACTIVE_INSTALLS.lock().unwrap().insert(name.clone(), token.clone());
+
+    let result = provider.install(app_handle, target_dir, token).await;
+
+    ACTIVE_INSTALLS.lock().unwrap().remove(&name);
+    result
+}
+
+/// Aborts the named install if one is running. Returns false if there was nothing to cancel.
+pub fn cancel_install(name: &str) -> bool {
+    match ACTIVE_INSTALLS.lock().unwrap().get(name) {
+        Some(token) => { token.cancel(); true }
+        None => false,
+    }
 }
 
 // --- Network Helpers ---
 
-fn get_http_client() -> Result<Client, String> {
+pub fn get_http_client() -> Result<Client, String> {
     Client::builder()
         .user_agent("Multiyt-dlp/2.0 (github.com/zqil/multiyt-dlp)")
         .build()
@@ -79,17 +119,45 @@ pub async fn get_latest_github_tag(repo: &str) -> Result<String, String> {
         .ok_or_else(|| "Could not find tag_name in response".to_string())
 }
 
-async fn download_file(url: &str, dest: &PathBuf, name: &str, app_handle: &AppHandle) -> Result<(), String> {
+/// Removes the `.part` file when `download_file` returns, whichever way it exits. After the
+/// rename into place there is nothing left to remove.
+struct PartFileGuard(PathBuf);
+
+impl Drop for PartFileGuard {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Streams `url` into `dest` via a `.part` file that is renamed on completion, so an
+/// aborted or cancelled download never replaces a working binary.
+async fn download_file(url: &str, dest: &PathBuf, name: &str, app_handle: &AppHandle, cancel: &CancellationToken) -> Result<(), String> {
     let client = get_http_client()?;
     let res = client.get(url).send().await.map_err(|e| e.to_string())?;
     
     let total_size = res.content_length().unwrap_or(0);
-    let mut file = File::create(dest).map_err(|e| e.to_string())?;
+    let part_path = PathBuf::from(format!("{}.part", dest.display()));
+    // Declared before `file` so it drops after it: the handle is closed before the delete (Windows)
+    let _part_guard = PartFileGuard(part_path.clone());
+    let mut file = File::create(&part_path).map_err(|e| e.to_string())?;
     let mut stream = res.bytes_stream();
     let mut downloaded: u64 = 0;
     let mut last_emit = 0;
+    let started = Instant::now();
+    let speed_units = app_handle.state::<Arc<ConfigManager>>().get_config().general.speed_units;
+
+    loop {
+        let item = tokio::select! {
+            _ = cancel.cancelled() => {
+                let _ = app_handle.emit_all("install-cancelled", InstallCancelledPayload { name: name.to_string() });
+                return Err(format!("Installation of {} was cancelled", name));
+            }
+            item = stream.next() => match item {
+                Some(item) => item,
+                None => break,
+            },
+        };
 
-    while let Some(item) = stream.next().await {
         let chunk = item.map_err(|e| e.to_string())?;
         file.write_all(&chunk).map_err(|e| e.to_string())?;
         downloaded += chunk.len() as u64;
@@ -99,14 +167,23 @@ async fn download_file(url: &str, dest: &PathBuf, name: &str, app_handle: &AppHa
             // Emit every 5% or when done to reduce IPC traffic
             if percentage >= last_emit + 5 || percentage == 100 {
                 last_emit = percentage;
+                let elapsed = started.elapsed().as_secs_f64();
+                let speed = if elapsed > 0.0 { downloaded as f64 / elapsed } else { 0.0 };
+                let eta = (speed > 0.0).then(|| format_eta((total_size.saturating_sub(downloaded) as f64 / speed) as u64));
+
                 let _ = app_handle.emit_all("install-progress", InstallProgressPayload {
                     name: name.to_string(),
                     percentage,
-                    status: "Downloading...".to_string()
+                    status: "Downloading...".to_string(),
+                    speed: Some(format_speed(speed, speed_units)),
+                    eta,
                 });
             }
         }
     }
+
+    drop(file);
+    fs::rename(&part_path, dest).map_err(|e| e.to_string())?;
     Ok(())
 }
 
@@ -171,6 +248,37 @@ fn extract_zip_finding_binary(zip_path: &PathBuf, target_dir: &PathBuf, binary_n
     Ok(())
 }
 
+/// Sets 0o755 on a binary; no-op on Windows.
+fn set_executable(path: &Path) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(path).map_err(|e| e.to_string())?.permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(path, perms).map_err(|e| e.to_string())?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Restores the execute bit on every managed binary present in `bin_dir` (lost e.g. when
+/// copied across filesystems or restored from a backup). Returns the binaries it touched.
+pub fn repair_bin_permissions(bin_dir: &Path) -> Result<Vec<String>, String> {
+    let providers: [Box<dyn DependencyProvider>; 3] = [Box::new(YtDlpProvider), Box::new(FfmpegProvider), Box::new(DenoProvider)];
+    let mut repaired = Vec::new();
+    for provider in &providers {
+        for binary in provider.get_binaries() {
+            let path = bin_dir.join(binary);
+            if path.is_file() {
+                set_executable(&path)?;
+                repaired.push(binary.to_string());
+            }
+        }
+    }
+    Ok(repaired)
+}
+
 fn extract_tar_xz_finding_binary(tar_path: &PathBuf, target_dir: &PathBuf, binary_names: &[&str]) -> Result<(), String> {
     let tar_gz = File::open(tar_path).map_err(|e| e.to_string())?;
     let tar = xz2::read::XzDecoder::new(tar_gz);
@@ -198,19 +306,16 @@ impl DependencyProvider for YtDlpProvider {
     fn get_binaries(&self) -> Vec<&str> {
         if cfg!(windows) { vec!["yt-dlp.exe"] } else { vec!["yt-dlp"] }
     }
-    async fn install(&self, app_handle: AppHandle, target_dir: PathBuf) -> Result<(), String> {
+    async fn install(&self, app_handle: AppHandle, target_dir: PathBuf, cancel: CancellationToken) -> Result<(), String> {
         let filename = self.get_binaries()[0];
         let target_path = target_dir.join(filename);
         
-        download_file(YT_DLP_URL, &target_path, "yt-dlp", &app_handle).await?;
+        download_file(YT_DLP_URL, &target_path, "yt-dlp", &app_handle, &cancel).await?;
         
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let mut perms = fs::metadata(&target_path).map_err(|e| e.to_string())?.permissions();
-            perms.set_mode(0o755);
-            fs::set_permissions(&target_path, perms).map_err(|e| e.to_string())?;
-        }
+        set_executable(&target_path)?;
+
+        // A new version may add or remove extractors
+        crate::commands::system::invalidate_supported_sites();
 
         Ok(())
     }
@@ -223,15 +328,15 @@ impl DependencyProvider for FfmpegProvider {
     fn get_binaries(&self) -> Vec<&str> {
         if cfg!(windows) { vec!["ffmpeg.exe", "ffprobe.exe"] } else { vec!["ffmpeg", "ffprobe"] }
     }
-    async fn install(&self, app_handle: AppHandle, target_dir: PathBuf) -> Result<(), String> {
+    async fn install(&self, app_handle: AppHandle, target_dir: PathBuf, cancel: CancellationToken) -> Result<(), String> {
         let archive_name = if cfg!(windows) || cfg!(target_os = "macos") { "ffmpeg.zip" } else { "ffmpeg.tar.xz" };
         let temp_dir = std::env::temp_dir();
         let archive_path = temp_dir.join(archive_name);
 
-        download_file(FFMPEG_URL, &archive_path, "ffmpeg", &app_handle).await?;
+        download_file(FFMPEG_URL, &archive_path, "ffmpeg", &app_handle, &cancel).await?;
 
         let _ = app_handle.emit_all("install-progress", InstallProgressPayload {
-            name: "ffmpeg".to_string(), percentage: 100, status: "Extracting...".to_string()
+            name: "ffmpeg".to_string(), percentage: 100, status: "Extracting...".to_string(), speed: None, eta: None
         });
 
         if archive_path.extension().unwrap_or_default() == "zip" {
@@ -252,13 +357,13 @@ impl DependencyProvider for DenoProvider {
     fn get_binaries(&self) -> Vec<&str> {
         if cfg!(windows) { vec!["deno.exe"] } else { vec!["deno"] }
     }
-    async fn install(&self, app_handle: AppHandle, target_dir: PathBuf) -> Result<(), String> {
+    async fn install(&self, app_handle: AppHandle, target_dir: PathBuf, cancel: CancellationToken) -> Result<(), String> {
         let archive_path = std::env::temp_dir().join("deno.zip");
 
-        download_file(DENO_URL, &archive_path, "js_runtime", &app_handle).await?;
+        download_file(DENO_URL, &archive_path, "js_runtime", &app_handle, &cancel).await?;
 
         let _ = app_handle.emit_all("install-progress", InstallProgressPayload {
-            name: "js_runtime".to_string(), percentage: 100, status: "Extracting...".to_string()
+            name: "js_runtime".to_string(), percentage: 100, status: "Extracting...".to_string(), speed: None, eta: None
         });
 
         extract_zip_finding_binary(&archive_path, &target_dir, &self.get_binaries())?;
@@ -289,10 +394,12 @@ pub async fn auto_update_yt_dlp(app_handle: AppHandle, bin_dir: PathBuf) -> Resu
     let _ = app_handle.emit_all("install-progress", InstallProgressPayload {
         name: "yt-dlp".to_string(),
         percentage: 0,
-        status: format!("Updating to {}...", remote_tag)
+        status: format!("Updating to {}...", remote_tag),
+        speed: None,
+        eta: None,
     });
     
-    provider.install(app_handle, bin_dir).await
+    run_install(&provider, app_handle, bin_dir).await
 }
 
 pub async fn manage_js_runtime(app_handle: AppHandle, bin_dir: PathBuf) -> Result<(), String> {
@@ -329,10 +436,12 @@ pub async fn manage_js_runtime(app_handle: AppHandle, bin_dir: PathBuf) -> Resul
     let _ = app_handle.emit_all("install-progress", InstallProgressPayload {
         name: "Portable Runtime".to_string(),
         percentage: 0,
-        status: format!("Syncing Deno {}...", clean_remote)
+        status: format!("Syncing Deno {}...", clean_remote),
+        speed: None,
+        eta: None,
     });
 
-    provider.install(app_handle, bin_dir).await
+    run_install(&provider, app_handle, bin_dir).await
 }
 
 pub async fn install_missing_ffmpeg(app_handle: AppHandle, bin_dir: PathBuf) -> Result<(), String> {
@@ -347,9 +456,9 @@ pub async fn install_missing_ffmpeg(app_handle: AppHandle, bin_dir: PathBuf) ->
 
     if !local_path.exists() {
          let _ = app_handle.emit_all("install-progress", InstallProgressPayload {
-            name: "ffmpeg".to_string(), percentage: 0, status: "Installing...".to_string()
+            name: "ffmpeg".to_string(), percentage: 0, status: "Installing...".to_string(), speed: None, eta: None
         });
-        provider.install(app_handle, bin_dir).await?;
+        run_install(&provider, app_handle, bin_dir).await?;
     }
     Ok(())
 }
@@ -373,14 +482,16 @@ pub async fn install_dep(name: String, app_handle: AppHandle) -> Result<(), Stri
         fs::create_dir_all(&bin_dir).map_err(|e| e.to_string())?;
     }
 
-    provider.install(app_handle.clone(), bin_dir).await?;
+    run_install(provider.as_ref(), app_handle.clone(), bin_dir).await?;
 
     let installed_name = provider.get_name();
 
     let _ = app_handle.emit_all("install-progress", InstallProgressPayload {
         name: installed_name, 
         percentage: 100, 
-        status: "Installed".to_string()
+        status: "Installed".to_string(),
+        speed: None,
+        eta: None,
     });
 
     Ok(())
@@ -12,6 +12,9 @@ pub enum AppError {
 
     #[error("Validation failed: {0}")]
     ValidationFailed(String),
+
+    #[error("Already downloading: {0}")]
+    JobAlreadyExists(String),
 }
 
 // Required to convert from std::io::Error
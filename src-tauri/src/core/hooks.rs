@@ -0,0 +1,126 @@
+use std::path::Path;
+use std::process::Stdio;
+use tauri::{AppHandle, Manager};
+use tokio::process::Command;
+use uuid::Uuid;
+
+use crate::models::{FileCleanedPayload, PostHookResultPayload};
+
+/// Max characters of hook stdout/stderr forwarded to the UI.
+const HOOK_OUTPUT_LIMIT: usize = 4000;
+
+/// Values substituted into the post-download hook's `{path}`, `{title}` and `{url}` placeholders.
+pub struct HookVariables {
+    pub path: String,
+    pub title: String,
+    pub url: String,
+}
+
+/// Splits a command line into arguments without involving a shell.
+/// Single or double quotes group words; there is no escape character so Windows paths stay intact.
+pub fn tokenize_command(command: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut quote: Option<char> = None;
+
+    for c in command.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => { quote = Some(c); in_token = true; }
+            None if c.is_whitespace() => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            None => { current.push(c); in_token = true; }
+        }
+    }
+
+    if quote.is_some() {
+        return Err("Unbalanced quote in hook command".to_string());
+    }
+    if in_token {
+        tokens.push(current);
+    }
+    if tokens.is_empty() {
+        return Err("Hook command is empty".to_string());
+    }
+    Ok(tokens)
+}
+
+fn tail(text: &[u8]) -> String {
+    let text = String::from_utf8_lossy(text);
+    let start = text.char_indices().rev().nth(HOOK_OUTPUT_LIMIT).map(|(i, _)| i).unwrap_or(0);
+    text[start..].to_string()
+}
+
+/// Runs the user's post-download hook for one file and emits `post-hook-result`.
+/// Placeholders are substituted per argument after tokenizing, so values containing
+/// spaces or shell metacharacters are passed through as single, inert arguments.
+pub async fn run_post_hook(app_handle: AppHandle, job_id: Uuid, template: String, vars: HookVariables) -> PostHookResultPayload {
+    let result = match tokenize_command(&template) {
+        Ok(tokens) => {
+            let args: Vec<String> = tokens.iter()
+                .map(|t| t.replace("{path}", &vars.path).replace("{title}", &vars.title).replace("{url}", &vars.url))
+                .collect();
+
+            let mut cmd = Command::new(&args[0]);
+            cmd.args(&args[1..])
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped());
+
+            #[cfg(target_os = "windows")]
+            { cmd.creation_flags(0x08000000); }
+
+            match cmd.output().await {
+                Ok(output) => PostHookResultPayload {
+                    job_id,
+                    success: output.status.success(),
+                    exit_code: output.status.code(),
+                    stdout: tail(&output.stdout),
+                    stderr: tail(&output.stderr),
+                },
+                Err(e) => PostHookResultPayload {
+                    job_id, success: false, exit_code: None, stdout: String::new(),
+                    stderr: format!("Failed to start hook: {}", e),
+                },
+            }
+        }
+        Err(e) => PostHookResultPayload {
+            job_id, success: false, exit_code: None, stdout: String::new(), stderr: e,
+        },
+    };
+
+    if result.success {
+        tracing::info!("Post-download hook succeeded for {}", job_id);
+    } else {
+        tracing::warn!("Post-download hook failed for {}: {}", job_id, result.stderr);
+    }
+
+    let _ = app_handle.emit_all("post-hook-result", result.clone());
+    result
+}
+
+/// `delete_after_hook`: removes the downloaded file once its hook succeeded and emits
+/// `file-cleaned`. Only call this for a successful hook; a file the hook already moved
+/// away is left alone, and only regular files are ever removed.
+pub fn delete_after_hook(app_handle: &AppHandle, job_id: Uuid, path: &Path) {
+    if path.as_os_str().is_empty() || !path.is_file() {
+        tracing::info!("Nothing to clean up for {}: {} is no longer there", job_id, path.display());
+        return;
+    }
+    match std::fs::remove_file(path) {
+        Ok(()) => {
+            tracing::info!("Deleted {} after its post-download hook", path.display());
+            let _ = app_handle.emit_all("file-cleaned", FileCleanedPayload {
+                job_id,
+                path: path.to_string_lossy().to_string(),
+            });
+        }
+        Err(e) => tracing::warn!("Could not delete {} after its post-download hook: {}", path.display(), e),
+    }
+}
@@ -0,0 +1,159 @@
+//! Optional local HTTP API for headless/remote control (`enable_http_api`).
+//! Every request needs `Authorization: Bearer <http_api_token>`.
+//!
+//! - `POST /download` with `{"url": ..., "format_preset"?, "video_resolution"?, "download_path"?, "priority"?}`
+//! - `GET /queue` returns the progress of every active job
+//! - `POST /cancel/<job id>`
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use hyper::body::HttpBody;
+use hyper::header::{AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::Deserialize;
+use tauri::{AppHandle, Manager};
+use uuid::Uuid;
+
+use crate::commands::downloader::queue_download;
+use crate::core::manager::JobManagerHandle;
+use crate::models::{DownloadFormatPreset, JobPriority, StartDownloadOptions};
+
+/// Request bodies above this are rejected; a download request is a few hundred bytes.
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+#[derive(Deserialize)]
+struct DownloadRequest {
+    url: String,
+    format_preset: Option<DownloadFormatPreset>,
+    video_resolution: Option<String>,
+    download_path: Option<String>,
+    priority: Option<JobPriority>,
+}
+
+fn json_response<T: serde::Serialize>(status: StatusCode, value: &T) -> Response<Body> {
+    let body = serde_json::to_vec(value).unwrap_or_default();
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Body> {
+    json_response(status, &serde_json::json!({ "error": message }))
+}
+
+/// Compares without returning early, so response timing doesn't leak how much of the token matched.
+fn token_matches(provided: &str, expected: &str) -> bool {
+    provided.len() == expected.len()
+        && provided.bytes().zip(expected.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+fn authorized(req: &Request<Body>, token: &str) -> bool {
+    req.headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|provided| token_matches(provided.trim(), token))
+}
+
+/// Reads the body up to `MAX_BODY_BYTES`: an oversized `Content-Length` is refused before reading,
+/// and a body without one (chunked) is cut off as soon as it goes past the limit.
+async fn read_body(req: Request<Body>) -> Result<Vec<u8>, Response<Body>> {
+    let too_large = || error_response(StatusCode::PAYLOAD_TOO_LARGE, "Request body too large");
+    let declared = req.headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if declared.is_some_and(|len| len > MAX_BODY_BYTES as u64) {
+        return Err(too_large());
+    }
+
+    let mut body = req.into_body();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| error_response(StatusCode::BAD_REQUEST, &e.to_string()))?;
+        if bytes.len() + chunk.len() > MAX_BODY_BYTES {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+async fn handle(app_handle: AppHandle, token: Arc<String>, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    if !authorized(&req, &token) {
+        return Ok(error_response(StatusCode::UNAUTHORIZED, "Missing or invalid bearer token"));
+    }
+
+    let manager = app_handle.state::<JobManagerHandle>();
+    let path = req.uri().path().trim_end_matches('/').to_string();
+
+    let response = match (req.method(), path.as_str()) {
+        (&Method::POST, "/download") => {
+            let bytes = match read_body(req).await {
+                Ok(bytes) => bytes,
+                Err(response) => return Ok(response),
+            };
+            let request: DownloadRequest = match serde_json::from_slice(&bytes) {
+                Ok(request) => request,
+                Err(e) => return Ok(error_response(StatusCode::BAD_REQUEST, &format!("Invalid JSON: {}", e))),
+            };
+
+            // Same path as the UI: omitted options fall back to the saved preferences
+            let options = StartDownloadOptions {
+                download_path: request.download_path,
+                format_preset: request.format_preset,
+                video_resolution: request.video_resolution,
+                priority: request.priority,
+                ..Default::default()
+            };
+            let result = queue_download(&app_handle, request.url, options).await;
+            match result {
+                Ok(job_ids) => json_response(StatusCode::ACCEPTED, &serde_json::json!({ "jobIds": job_ids })),
+                Err(e) => json_response(StatusCode::UNPROCESSABLE_ENTITY, &e),
+            }
+        }
+        (&Method::GET, "/queue") => {
+            json_response(StatusCode::OK, &manager.current_progress().await)
+        }
+        (&Method::POST, p) if p.starts_with("/cancel/") => {
+            match Uuid::parse_str(&p["/cancel/".len()..]) {
+                Ok(id) => {
+                    manager.cancel_job(id).await;
+                    Response::builder().status(StatusCode::NO_CONTENT).body(Body::empty()).unwrap()
+                }
+                Err(_) => error_response(StatusCode::BAD_REQUEST, "Invalid job id"),
+            }
+        }
+        _ => error_response(StatusCode::NOT_FOUND, "Not found"),
+    };
+    Ok(response)
+}
+
+/// Serves the API until the app exits. Config changes take effect on the next start.
+pub async fn serve(app_handle: AppHandle, bind: SocketAddr, token: String) {
+    let token = Arc::new(token);
+    let make_service = make_service_fn(move |_conn| {
+        let app_handle = app_handle.clone();
+        let token = token.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| handle(app_handle.clone(), token.clone(), req)))
+        }
+    });
+
+    let server = match Server::try_bind(&bind) {
+        Ok(builder) => builder.serve(make_service),
+        Err(e) => {
+            tracing::error!("HTTP API could not bind {}: {}", bind, e);
+            return;
+        }
+    };
+    tracing::info!("HTTP API listening on {}", bind);
+    if let Err(e) = server.await {
+        tracing::error!("HTTP API stopped: {}", e);
+    }
+}
@@ -1,6 +1,8 @@
 use std::fs;
+use std::path::{Path, PathBuf};
 use tracing::{info};
 use tracing_subscriber::{
+    filter::LevelFilter,
     fmt, 
     prelude::*, 
     reload, 
@@ -13,9 +15,16 @@ use tracing_appender::non_blocking::WorkerGuard;
 // Generic params: <FilterType, RegistryType>
 pub type LogHandle = reload::Handle<EnvFilter, Registry>;
 
+/// Prefix of the rolling WARN/ERROR-only log file.
+const ERROR_LOG_PREFIX: &str = "error.log";
+/// Prefix of the rolling full log file.
+const APP_LOG_PREFIX: &str = "app.log";
+
 pub struct LogManager {
-    // We must keep the guard alive, otherwise file logging stops immediately
+    // We must keep the guards alive, otherwise file logging stops immediately
     _guard: WorkerGuard,
+    _error_guard: WorkerGuard,
+    log_dir: PathBuf,
     // The handle allows us to swap the filter (log level) at runtime
     reload_handle: LogHandle,
 }
@@ -31,9 +40,12 @@ impl LogManager {
         }
 
         // 2. File Appender (Rolling Daily)
-        let file_appender = tracing_appender::rolling::daily(&log_dir, "app.log");
+        let file_appender = tracing_appender::rolling::daily(&log_dir, APP_LOG_PREFIX);
         let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
 
+        let error_appender = tracing_appender::rolling::daily(&log_dir, ERROR_LOG_PREFIX);
+        let (non_blocking_errors, error_guard) = tracing_appender::non_blocking(error_appender);
+
         // 3. Layers
         
         // Layer A: JSON File Output
@@ -44,7 +56,16 @@ impl LogManager {
             .with_file(true)
             .with_line_number(true);
 
-        // Layer B: Pretty Console Output
+        // Layer B: JSON File Output, WARN/ERROR only (small file for bug reports)
+        let error_file_layer = fmt::layer()
+            .json()
+            .with_writer(non_blocking_errors)
+            .with_target(true)
+            .with_file(true)
+            .with_line_number(true)
+            .with_filter(LevelFilter::WARN);
+
+        // Layer C: Pretty Console Output
         let stdout_layer = fmt::layer()
             .pretty()
             .with_writer(std::io::stdout);
@@ -62,6 +83,7 @@ impl LogManager {
         tracing_subscriber::registry()
             .with(filter_layer) // Apply filter first
             .with(file_layer)
+            .with(error_file_layer)
             .with(stdout_layer)
             .init();
 
@@ -70,6 +92,8 @@ impl LogManager {
 
         Self {
             _guard: guard,
+            _error_guard: error_guard,
+            log_dir,
             reload_handle,
         }
     }
@@ -86,6 +110,42 @@ impl LogManager {
         Ok(())
     }
 
+    /// Returns the last `max_lines` entries of the most recent error-only log file.
+    pub fn read_error_log(&self, max_lines: usize) -> Result<String, String> {
+        latest_log_tail(&self.log_dir, ERROR_LOG_PREFIX, max_lines)
+    }
+
+    pub fn log_dir(&self) -> &Path {
+        &self.log_dir
+    }
+
+    /// Deletes rotated log files, keeping the newest file of each log so the open appenders
+    /// keep writing to a valid handle. Returns the number of bytes freed.
+    pub fn clear_old_logs(&self) -> Result<u64, String> {
+        let entries: Vec<fs::DirEntry> = fs::read_dir(&self.log_dir)
+            .map_err(|e| format!("Failed to read log directory: {}", e))?
+            .flatten()
+            .filter(|entry| entry.path().is_file())
+            .collect();
+
+        let newest = |prefix: &str| entries.iter()
+            .map(|entry| entry.file_name())
+            .filter(|name| name.to_string_lossy().starts_with(prefix))
+            .max();
+        let keep = [newest(APP_LOG_PREFIX), newest(ERROR_LOG_PREFIX)];
+
+        let mut freed = 0;
+        for entry in &entries {
+            if keep.iter().flatten().any(|name| *name == entry.file_name()) { continue; }
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            if fs::remove_file(entry.path()).is_ok() {
+                freed += size;
+            }
+        }
+        info!("Cleared old logs ({} bytes)", freed);
+        Ok(freed)
+    }
+
     /// Helper to construct a filter string that silences dependencies
     fn get_filter_string(level: &str) -> String {
         // "info,tao=error,wry=error" means:
@@ -94,4 +154,28 @@ impl LogManager {
         // - crate 'wry' is restricted to ERROR
         format!("{},tao=error,wry=error", level)
     }
-}
\ No newline at end of file
+}
+
+/// Last `max_lines` lines of the full app log in `log_dir`, for crash reports.
+pub fn app_log_tail(log_dir: &Path, max_lines: usize) -> Result<String, String> {
+    latest_log_tail(log_dir, APP_LOG_PREFIX, max_lines)
+}
+
+fn latest_log_tail(log_dir: &Path, prefix: &str, max_lines: usize) -> Result<String, String> {
+    let latest = fs::read_dir(log_dir)
+        .map_err(|e| format!("Failed to read log directory: {}", e))?
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(prefix))
+        .max_by_key(|entry| entry.file_name());
+
+    let path = match latest {
+        Some(entry) => entry.path(),
+        None => return Ok(String::new()),
+    };
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read log: {}", e))?;
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.len().saturating_sub(max_lines);
+    Ok(lines[start..].join("\n"))
+}
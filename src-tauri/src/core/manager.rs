@@ -1,20 +1,25 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
+use futures_util::FutureExt;
 use tokio::sync::{mpsc, oneshot};
-use tokio::time::{self, Duration};
+use tokio::time::{self, Duration, Instant};
 use tauri::{AppHandle, Manager};
 use uuid::Uuid;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::models::{
-    Job, JobStatus, QueuedJob, JobMessage, 
+    ActorStateDump, Job, JobSearchResult, JobStatus, JobPriority, QueuedJob, JobMessage, 
     DownloadProgressPayload, BatchProgressPayload, 
-    DownloadCompletePayload, DownloadErrorPayload
+    DownloadCancelledPayload, DownloadCompletePayload, DownloadSkippedPayload, DownloadErrorPayload, CompletedEntry, DiskSpacePayload, GlobalPausePayload, BatchCancelledPayload, QueueHaltedPayload, QueueStatePayload
 };
 use crate::config::ConfigManager;
-use crate::core::process::run_download_process;
+use crate::core::process::{job_target_dir, job_temp_dir, run_download_process, url_host};
+use crate::core::util::parse_byte_size;
 use crate::core::native;
+use crate::core::error::AppError;
+use crate::commands::system::{available_space, dir_size};
 
 /// The "Handle" is what we pass around in the Tauri state.
 /// It sends messages to the running Actor loop.
@@ -35,16 +40,47 @@ impl JobManagerHandle {
         Self { sender }
     }
 
-    pub async fn add_job(&self, job: QueuedJob) -> Result<(), String> {
+    pub async fn add_job(&self, job: QueuedJob) -> Result<(), AppError> {
         let (tx, rx) = oneshot::channel();
-        let _ = self.sender.send(JobMessage::AddJob { job, resp: tx }).await;
-        rx.await.map_err(|_| "Actor closed".to_string())?
+        let _ = self.sender.send(JobMessage::AddJob { job: Box::new(job), resp: tx }).await;
+        rx.await.map_err(|_| AppError::IoError("Actor closed".to_string()))?
     }
 
     pub async fn cancel_job(&self, id: Uuid) {
         let _ = self.sender.send(JobMessage::CancelJob { id }).await;
     }
 
+    pub async fn cancel_batch(&self, batch_id: Uuid) -> Vec<Uuid> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(JobMessage::CancelBatch { batch_id, resp: tx }).await;
+        rx.await.unwrap_or_default()
+    }
+
+    pub async fn remove_job(&self, id: Uuid) -> Result<(), String> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(JobMessage::RemoveJob { id, resp: tx }).await;
+        rx.await.map_err(|_| "Actor closed".to_string())?
+    }
+
+    /// Whether a pending or running job already targets `url` (compared normalized).
+    pub async fn is_url_active(&self, url: String) -> bool {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(JobMessage::IsUrlActive { url, resp: tx }).await;
+        rx.await.unwrap_or(false)
+    }
+
+    pub async fn job_url(&self, id: Uuid) -> Option<String> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(JobMessage::GetJobUrl { id, resp: tx }).await;
+        rx.await.unwrap_or(None)
+    }
+
+    pub async fn update_job_metadata(&self, id: Uuid, title: String, thumbnail: Option<String>) -> Result<(), String> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(JobMessage::UpdateJobMetadata { id, title, thumbnail, resp: tx }).await;
+        rx.await.map_err(|_| "Actor closed".to_string())?
+    }
+
     pub async fn get_pending_count(&self) -> u32 {
         let (tx, rx) = oneshot::channel();
         let _ = self.sender.send(JobMessage::GetPendingCount(tx)).await;
@@ -53,15 +89,111 @@ impl JobManagerHandle {
 
     pub async fn resume_pending(&self) -> Vec<QueuedJob> {
         let (tx, rx) = oneshot::channel();
-        let _ = self.sender.send(JobMessage::ResumePending(tx)).await;
+        let _ = self.sender.send(JobMessage::ResumePending { skip_missing_paths: false, resp: tx }).await;
+        rx.await.unwrap_or_default()
+    }
+
+    /// Startup variant of `resume_pending`: jobs whose download folder is gone (e.g. an
+    /// unplugged drive) stay persisted for a manual resume instead of failing immediately.
+    pub async fn auto_resume_pending(&self) -> Vec<QueuedJob> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(JobMessage::ResumePending { skip_missing_paths: true, resp: tx }).await;
         rx.await.unwrap_or_default()
     }
 
     pub async fn clear_pending(&self) {
         let _ = self.sender.send(JobMessage::ClearPending).await;
     }
+
+    pub async fn current_progress(&self) -> Vec<DownloadProgressPayload> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(JobMessage::GetCurrentProgress(tx)).await;
+        rx.await.unwrap_or_default()
+    }
+
+    pub async fn debug_dump(&self) -> Result<ActorStateDump, String> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(JobMessage::DebugDump(tx)).await;
+        rx.await.map_err(|_| "Actor closed".to_string())
+    }
+
+    pub async fn average_speed(&self) -> Option<f64> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(JobMessage::GetAverageSpeed(tx)).await;
+        rx.await.unwrap_or(None)
+    }
+
+    pub async fn set_priority(&self, id: Uuid, priority: JobPriority) -> Result<(), String> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(JobMessage::SetPriority { id, priority, resp: tx }).await;
+        rx.await.map_err(|_| "Actor closed".to_string())?
+    }
+
+    pub async fn completed_entries(&self, video_ids: Vec<String>) -> Vec<CompletedEntry> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(JobMessage::GetCompletedEntries { video_ids, resp: tx }).await;
+        rx.await.unwrap_or_default()
+    }
+
+    pub async fn start_queue(&self, ids: Option<Vec<Uuid>>) -> u32 {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(JobMessage::StartQueue { ids, resp: tx }).await;
+        rx.await.unwrap_or(0)
+    }
+
+    pub async fn set_job_label(&self, id: Uuid, label: Option<String>) -> Result<(), String> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(JobMessage::SetJobLabel { id, label, resp: tx }).await;
+        rx.await.map_err(|_| "Actor closed".to_string())?
+    }
+
+    pub async fn search_jobs(&self, query: String) -> Vec<JobSearchResult> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(JobMessage::SearchJobs { query, resp: tx }).await;
+        rx.await.unwrap_or_default()
+    }
+
+    pub async fn resume_queue(&self) {
+        let _ = self.sender.send(JobMessage::ResumeQueue).await;
+    }
+
+    pub async fn pause_all(&self) {
+        let _ = self.sender.send(JobMessage::PauseAll).await;
+    }
+
+    pub async fn resume_all(&self) {
+        let _ = self.sender.send(JobMessage::ResumeAll).await;
+    }
+
+    pub async fn set_queue_download_path(&self, path: String) -> u32 {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(JobMessage::SetQueueDownloadPath { path, resp: tx }).await;
+        rx.await.unwrap_or(0)
+    }
+
+    pub async fn clear_temp(&self) -> Result<u64, String> {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(JobMessage::ClearTemp(tx)).await;
+        rx.await.map_err(|_| "Actor closed".to_string())?
+    }
 }
 
+/// Progress of one start_download batch, used to write its playlist file at the end.
+struct BatchTracker {
+    title: Option<String>,
+    size: u32,
+    write_m3u: bool,
+    settled: HashSet<Uuid>,
+    // (batch_index, final path) of each successful job
+    completed: Vec<(u32, String)>,
+}
+
+/// Smoothing factor for the session speed average (higher = reacts faster).
+const SPEED_EMA_ALPHA: f64 = 0.05;
+
+/// How often a queue paused for low disk space looks again.
+const DISK_RECHECK_INTERVAL: Duration = Duration::from_secs(10);
+
 struct JobManagerActor {
     app_handle: AppHandle,
     receiver: mpsc::Receiver<JobMessage>,
@@ -71,18 +203,44 @@ struct JobManagerActor {
     jobs: HashMap<Uuid, Job>,
     queue: VecDeque<QueuedJob>,
     persistence_registry: HashMap<Uuid, QueuedJob>,
+    // Finished downloads per (video id, format), so a different format isn't treated as a duplicate
+    completed_index: Vec<CompletedEntry>,
 
     // Concurrency
-    active_network_jobs: u32,
+    // Jobs holding a download (network) slot; a set so each job releases its slot at most once
+    network_holders: HashSet<Uuid>,
+    // Retries waiting to take their released slot back; served before new jobs start
+    network_waiters: VecDeque<(Uuid, oneshot::Sender<()>)>,
     active_process_instances: u32,
     completed_session_count: u32,
+    // Set when a job fails with `stop_queue_on_error`; no new jobs start until resumed
+    queue_halted: bool,
+    // Set while the next job's drive is below `min_free_space`; re-checked every DISK_RECHECK_INTERVAL
+    disk_space_paused: bool,
+    last_disk_check: Instant,
+    // Set by pause_all; running jobs are suspended (keeping their slots) and nothing new starts
+    global_paused: bool,
+    suspended: HashSet<Uuid>,
+
+    // Session Stats
+    avg_speed_bps: Option<f64>,
 
     // Batching Buffer
     pending_updates: HashMap<Uuid, DownloadProgressPayload>,
+    // Last payload per active job, kept across flushes so a reloaded UI can rehydrate
+    last_payload: HashMap<Uuid, DownloadProgressPayload>,
+    batches: HashMap<Uuid, BatchTracker>,
+    // job id -> (batch id, index in batch), for jobs that belong to a batch
+    job_batches: HashMap<Uuid, (Uuid, u32)>,
 }
 
 impl JobManagerActor {
     fn new(app_handle: AppHandle, receiver: mpsc::Receiver<JobMessage>, self_sender: mpsc::Sender<JobMessage>) -> Self {
+        let persist_pause = app_handle.state::<Arc<ConfigManager>>().get_config().general.persist_global_pause;
+        let global_paused = persist_pause && Self::get_pause_marker_path().exists();
+        if global_paused {
+            tracing::info!("Queue is still paused from the last session; resume_all to continue");
+        }
         Self {
             app_handle,
             receiver,
@@ -90,10 +248,21 @@ impl JobManagerActor {
             jobs: HashMap::new(),
             queue: VecDeque::new(),
             persistence_registry: HashMap::new(),
-            active_network_jobs: 0,
+            completed_index: Self::load_completed_index(),
+            network_holders: HashSet::new(),
+            network_waiters: VecDeque::new(),
             active_process_instances: 0,
             completed_session_count: 0,
+            queue_halted: false,
+            disk_space_paused: false,
+            last_disk_check: Instant::now(),
+            global_paused,
+            suspended: HashSet::new(),
+            avg_speed_bps: None,
             pending_updates: HashMap::new(),
+            last_payload: HashMap::new(),
+            batches: HashMap::new(),
+            job_batches: HashMap::new(),
         }
     }
 
@@ -102,6 +271,12 @@ impl JobManagerActor {
         home.join(".multiyt-dlp").join("jobs.json")
     }
 
+    /// Present while the queue is globally paused and `persist_global_pause` is on.
+    fn get_pause_marker_path() -> PathBuf {
+        let home = dirs::home_dir().expect("Could not find home directory");
+        home.join(".multiyt-dlp").join("queue_paused")
+    }
+
     fn save_state(&self) {
         let path = Self::get_persistence_path();
         // Clone the data needed for saving so we can move it into the async block.
@@ -115,6 +290,98 @@ impl JobManagerActor {
         });
     }
 
+    fn get_completed_index_path() -> PathBuf {
+        let home = dirs::home_dir().expect("Could not find home directory");
+        home.join(".multiyt-dlp").join("completed_index.json")
+    }
+
+    fn load_completed_index() -> Vec<CompletedEntry> {
+        fs::read_to_string(Self::get_completed_index_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn record_completed(&mut self, job: &QueuedJob, output_path: &str) {
+        let Some(video_id) = job.video_id.clone() else { return; };
+
+        self.completed_index.retain(|e| !(e.video_id == video_id && e.format_preset == job.format_preset));
+        self.completed_index.push(CompletedEntry {
+            video_id,
+            format_preset: job.format_preset.clone(),
+            output_path: output_path.to_string(),
+            completed_at: chrono::Utc::now().timestamp(),
+            label: job.label.clone(),
+        });
+
+        let path = Self::get_completed_index_path();
+        let entries = self.completed_index.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Ok(json) = serde_json::to_string_pretty(&entries) {
+                 let _ = tokio::fs::write(path, json).await;
+            }
+        });
+    }
+
+    /// Kills the job's process (if running), drops it from the queue and persistence and
+    /// settles its batch slot. Callers save state and notify the UI.
+    fn cancel(&mut self, id: Uuid) {
+        if let Some(job) = self.jobs.get(&id) {
+            if let Some(pid) = job.pid {
+                // A stopped process can't act on SIGINT until it is continued
+                if self.suspended.remove(&id) {
+                    set_process_suspended(pid, false);
+                }
+                self.kill_process(pid);
+            }
+        }
+
+        if let Some(job) = self.jobs.get_mut(&id) {
+            job.status = JobStatus::Cancelled;
+        }
+
+        // Drop it from the queue too, or a held job would linger there until released
+        self.queue.retain(|j| j.id != id);
+        // A retry waiting for its slot sees the reply dropped and exits
+        self.network_waiters.retain(|(waiting, _)| *waiting != id);
+
+        self.persistence_registry.remove(&id);
+        self.last_payload.remove(&id);
+        self.settle_batch_member(id, None);
+    }
+
+    fn track_batch(&mut self, job: &QueuedJob) {
+        let Some(batch_id) = job.batch_id else { return; };
+        self.job_batches.insert(job.id, (batch_id, job.batch_index));
+        self.batches.entry(batch_id).or_insert_with(|| BatchTracker {
+            title: job.batch_title.clone(),
+            size: job.batch_size,
+            write_m3u: job.write_m3u,
+            settled: HashSet::new(),
+            completed: Vec::new(),
+        });
+    }
+
+    /// Marks a batch member finished (with its output path on success). Once every member
+    /// has settled the batch is dropped, writing its .m3u8 first if requested.
+    fn settle_batch_member(&mut self, id: Uuid, output_path: Option<&str>) {
+        let Some((batch_id, index)) = self.job_batches.get(&id).copied() else { return; };
+        let Some(batch) = self.batches.get_mut(&batch_id) else { return; };
+
+        // A cancelled job may still report an error when its process exits; count it once
+        if !batch.settled.insert(id) { return; }
+        if let Some(path) = output_path {
+            batch.completed.push((index, path.to_string()));
+        }
+        if (batch.settled.len() as u32) < batch.size { return; }
+
+        let Some(batch) = self.batches.remove(&batch_id) else { return; };
+        self.job_batches.retain(|_, (b, _)| *b != batch_id);
+        if batch.write_m3u && batch.size > 1 && !batch.completed.is_empty() {
+            write_batch_playlist(batch);
+        }
+    }
+
     async fn run(mut self) {
         // Tick for UI updates (200ms) to prevent frontend flooding
         let mut interval = time::interval(Duration::from_millis(200));
@@ -123,13 +390,20 @@ impl JobManagerActor {
             tokio::select! {
                 // 1. Handle Messages
                 Some(msg) = self.receiver.recv() => {
-                    self.handle_message(msg).await;
+                    // A bug in one handler must not take the whole download engine down with it.
+                    // State may be off afterwards (see debug_dump_state), but the queue keeps running.
+                    if AssertUnwindSafe(self.handle_message(msg)).catch_unwind().await.is_err() {
+                        tracing::error!("Job manager recovered from a panic while handling a message");
+                    }
                 }
 
                 // 2. Batch Emit Tick
                 _ = interval.tick() => {
                     self.flush_updates();
                     self.update_native_ui();
+                    if self.disk_space_paused && self.last_disk_check.elapsed() >= DISK_RECHECK_INTERVAL {
+                        self.process_queue();
+                    }
                 }
             }
         }
@@ -138,40 +412,65 @@ impl JobManagerActor {
     async fn handle_message(&mut self, msg: JobMessage) {
         match msg {
             JobMessage::AddJob { job, resp } => {
+                let job = *job;
+                let reject_duplicates = self.app_handle.state::<Arc<ConfigManager>>().get_config().general.reject_duplicate_urls;
+                let normalized = normalize_url(&job.url);
+
                 if self.jobs.contains_key(&job.id) {
-                    let _ = resp.send(Err("Job already exists".into()));
+                    let _ = resp.send(Err(AppError::JobAlreadyExists(job.url.clone())));
+                } else if reject_duplicates && self.has_active_url(&normalized) {
+                    // Count the rejected entry as settled so its batch can still complete
+                    self.track_batch(&job);
+                    self.settle_batch_member(job.id, None);
+                    let _ = resp.send(Err(AppError::JobAlreadyExists(job.url.clone())));
                 } else {
-                    let j = Job::new(job.id, job.url.clone());
+                    let mut j = Job::new(job.id, job.url.clone(), job.title.clone());
+                    j.label = job.label.clone();
                     self.jobs.insert(job.id, j);
+                    self.track_batch(&job);
                     self.persistence_registry.insert(job.id, job.clone());
-                    self.queue.push_back(job);
+                    self.enqueue(job);
                     self.save_state();
                     self.process_queue();
                     let _ = resp.send(Ok(()));
                 }
             },
             JobMessage::CancelJob { id } => {
-                // Kill Process
-                if let Some(job) = self.jobs.get(&id) {
-                    if let Some(pid) = job.pid {
-                        self.kill_process(pid);
-                    }
-                }
-                
-                // Update Status
-                if let Some(job) = self.jobs.get_mut(&id) {
-                    job.status = JobStatus::Cancelled;
-                }
-
-                // Clean Persistence
-                self.persistence_registry.remove(&id);
+                self.cancel(id);
                 self.save_state();
 
                 // Notify Front End immediately (cancellation is urgent)
-                let _ = self.app_handle.emit_all("download-error", DownloadErrorPayload {
-                    job_id: id,
-                    error: "Cancelled by user".to_string()
-                });
+                let _ = self.app_handle.emit_all("download-cancelled", DownloadCancelledPayload { job_id: id });
+            },
+            JobMessage::CancelBatch { batch_id, resp } => {
+                let ids: Vec<Uuid> = self.job_batches.iter()
+                    .filter(|(_, (b, _))| *b == batch_id)
+                    .map(|(id, _)| *id)
+                    .filter(|id| self.jobs.get(id).is_some_and(|j| matches!(j.status, JobStatus::Pending | JobStatus::Downloading)))
+                    .collect();
+                for id in &ids {
+                    self.cancel(*id);
+                }
+                if !ids.is_empty() {
+                    self.save_state();
+                    tracing::info!("Cancelled {} job(s) of batch {}", ids.len(), batch_id);
+                    let _ = self.app_handle.emit_all("batch-cancelled", BatchCancelledPayload { batch_id, job_ids: ids.clone() });
+                }
+                let _ = resp.send(ids);
+            },
+            JobMessage::RemoveJob { id, resp } => {
+                let active = self.jobs.get(&id)
+                    .is_some_and(|j| matches!(j.status, JobStatus::Pending | JobStatus::Downloading));
+                if active {
+                    let _ = resp.send(Err("Cancel the job before removing it".into()));
+                } else {
+                    self.jobs.remove(&id);
+                    self.last_payload.remove(&id);
+                    if self.persistence_registry.remove(&id).is_some() {
+                        self.save_state();
+                    }
+                    let _ = resp.send(Ok(()));
+                }
             },
             JobMessage::ProcessStarted { id, pid } => {
                 if let Some(job) = self.jobs.get_mut(&id) {
@@ -181,57 +480,113 @@ impl JobManagerActor {
                     } else {
                         job.pid = Some(pid);
                         job.status = JobStatus::Downloading;
+                        // Spawned just before pause_all; hold it with the rest
+                        if self.global_paused && set_process_suspended(pid, true) {
+                            self.suspended.insert(id);
+                        }
                     }
                 }
             },
-            JobMessage::UpdateProgress { id, percentage, speed, eta, filename, phase } => {
+            JobMessage::UpdateProgress { id, percentage, speed, eta, filename, phase, speed_bps } => {
+                if let Some(bps) = speed_bps.filter(|s| s.is_finite() && *s > 0.0) {
+                    self.avg_speed_bps = Some(match self.avg_speed_bps {
+                        Some(avg) => avg + SPEED_EMA_ALPHA * (bps - avg),
+                        None => bps,
+                    });
+                }
                 if let Some(job) = self.jobs.get_mut(&id) {
                     job.progress = percentage;
+                    if filename.is_some() {
+                        job.title = filename.clone();
+                    }
                     // We don't emit here. We push to buffer.
-                    self.pending_updates.insert(id, DownloadProgressPayload {
+                    let payload = DownloadProgressPayload {
                         job_id: id,
                         percentage,
                         speed,
                         eta,
                         filename,
                         phase: Some(phase)
-                    });
+                    };
+                    self.last_payload.insert(id, payload.clone());
+                    self.pending_updates.insert(id, payload);
                 }
             },
-            JobMessage::JobCompleted { id, output_path } => {
+            JobMessage::JobCompleted { id, output_path, download_dir, filesize, format_id, container, note } => {
+                let mut label = None;
                 if let Some(job) = self.jobs.get_mut(&id) {
                     job.status = JobStatus::Completed;
                     job.progress = 100.0;
+                    label = job.label.clone();
                 }
-                self.persistence_registry.remove(&id);
+                // An empty path means nothing was written (see `note`); there is no file to index
+                let written = Some(output_path.as_str()).filter(|p| !p.is_empty());
+                if let Some(queued) = self.persistence_registry.remove(&id) {
+                    if let Some(path) = written { self.record_completed(&queued, path); }
+                }
+                self.settle_batch_member(id, written);
+                self.last_payload.remove(&id);
                 self.save_state();
 
                 let _ = self.app_handle.emit_all("download-complete", DownloadCompletePayload {
                     job_id: id,
                     output_path,
+                    download_dir,
+                    filesize,
+                    format_id,
+                    container,
+                    note,
+                    label,
                 });
             },
-            JobMessage::JobError { id, error } => {
+            JobMessage::JobSkipped { id, output_path, reason } => {
                 if let Some(job) = self.jobs.get_mut(&id) {
-                    job.status = JobStatus::Error;
+                    job.status = JobStatus::Skipped;
+                    job.progress = 100.0;
+                }
+                self.persistence_registry.remove(&id);
+                // The existing file still belongs to the batch (e.g. for its .m3u8)
+                self.settle_batch_member(id, Some(&output_path));
+                self.last_payload.remove(&id);
+                self.save_state();
+
+                let _ = self.app_handle.emit_all("download-skipped", DownloadSkippedPayload { job_id: id, output_path, reason });
+            },
+            JobMessage::JobError { id, error, code, hint } => {
+                // A killed process exits non-zero after CancelJob already told the UI (and the
+                // job may since have been removed), so only live jobs turn into errors
+                match self.jobs.get_mut(&id) {
+                    Some(job) if job.status != JobStatus::Cancelled => job.status = JobStatus::Error,
+                    _ => return,
                 }
+
+                let stop_on_error = self.app_handle.state::<Arc<ConfigManager>>().get_config().general.stop_queue_on_error;
+                if stop_on_error && !self.queue_halted {
+                    // Waiting jobs stay queued and persisted; they start again on resume_queue
+                    self.queue_halted = true;
+                    let _ = self.app_handle.emit_all("queue-halted", QueueHaltedPayload {
+                        failed_job_id: id,
+                        pending: self.queue.len() as u32,
+                    });
+                }
+                self.last_payload.remove(&id);
+                self.settle_batch_member(id, None);
                 // Persistence kept for retry
                 let _ = self.app_handle.emit_all("download-error", DownloadErrorPayload {
                     job_id: id,
                     error,
+                    error_code: code,
+                    hint,
                 });
             },
-            JobMessage::WorkerFinished => {
+            JobMessage::WorkerFinished { id } => {
                 if self.active_process_instances > 0 {
                     self.active_process_instances -= 1;
                     self.completed_session_count += 1;
                 }
-                
-                // Release network slot conservatively (though process logic usually manages this via phase)
-                // If a worker finishes, it definitely releases network if it was holding it
-                if self.active_network_jobs > 0 {
-                    self.active_network_jobs -= 1;
-                }
+                // No-op if NetworkFinished already released it during post-processing
+                self.network_holders.remove(&id);
+                self.suspended.remove(&id);
 
                 if self.active_process_instances == 0 {
                     self.trigger_finished_notification();
@@ -244,14 +599,16 @@ impl JobManagerActor {
                 if path.exists() {
                      if let Ok(content) = fs::read_to_string(path) {
                          if let Ok(jobs) = serde_json::from_str::<Vec<QueuedJob>>(&content) {
-                             let _ = tx.send(jobs.len() as u32);
+                             // Jobs already resumed this session (e.g. auto-resume) aren't pending
+                             let pending = jobs.iter().filter(|j| !self.jobs.contains_key(&j.id)).count();
+                             let _ = tx.send(pending as u32);
                              return;
                          }
                      }
                 }
                 let _ = tx.send(0);
             },
-            JobMessage::ResumePending(tx) => {
+            JobMessage::ResumePending { skip_missing_paths, resp: tx } => {
                 let path = Self::get_persistence_path();
                 let mut resumed = Vec::new();
                 if path.exists() {
@@ -259,11 +616,20 @@ impl JobManagerActor {
                         if let Ok(jobs) = serde_json::from_str::<Vec<QueuedJob>>(&content) {
                             for job in jobs {
                                 // Re-inject into state
+                                let path_missing = job.download_path.as_deref()
+                                    .is_some_and(|p| !p.trim().is_empty() && !Path::new(p).exists());
+                                if skip_missing_paths && path_missing {
+                                    tracing::warn!("Not resuming job {}: download folder no longer exists", job.id);
+                                    continue;
+                                }
                                 if !self.jobs.contains_key(&job.id) {
-                                    self.jobs.insert(job.id, Job::new(job.id, job.url.clone()));
+                                    let mut j = Job::new(job.id, job.url.clone(), job.title.clone());
+                                    j.label = job.label.clone();
+                                    self.jobs.insert(job.id, j);
+                                    self.track_batch(&job);
                                     self.persistence_registry.insert(job.id, job.clone());
                                     // Important: Queue it!
-                                    self.queue.push_back(job.clone());
+                                    self.enqueue(job.clone());
                                     resumed.push(job);
                                 }
                             }
@@ -277,6 +643,205 @@ impl JobManagerActor {
                 let path = Self::get_persistence_path();
                 if path.exists() { let _ = fs::remove_file(path); }
                 self.clean_temp_directory();
+            },
+            JobMessage::GetCurrentProgress(tx) => {
+                let _ = tx.send(self.last_payload.values().cloned().collect());
+            },
+            JobMessage::GetAverageSpeed(tx) => {
+                let _ = tx.send(self.avg_speed_bps);
+            },
+            JobMessage::NetworkFinished { id } => {
+                if self.network_holders.remove(&id) {
+                    self.process_queue();
+                }
+            },
+            JobMessage::NetworkStarted { id, resp } => {
+                self.network_waiters.push_back((id, resp));
+                self.process_queue();
+            },
+            JobMessage::DebugDump(tx) => {
+                let _ = tx.send(ActorStateDump {
+                    active_network_jobs: self.network_holders.len() as u32,
+                    network_job_ids: self.network_holders.iter().copied().collect(),
+                    active_process_instances: self.active_process_instances,
+                    completed_session_count: self.completed_session_count,
+                    queue_halted: self.queue_halted,
+                    disk_space_paused: self.disk_space_paused,
+                    global_paused: self.global_paused,
+                    suspended_job_ids: self.suspended.iter().copied().collect(),
+                    queue_len: self.queue.len(),
+                    persistence_registry_len: self.persistence_registry.len(),
+                    open_batches: self.batches.len(),
+                    jobs: self.jobs.values().cloned().collect(),
+                });
+            },
+            JobMessage::SetPriority { id, priority, resp } => {
+                match self.queue.iter().position(|j| j.id == id) {
+                    Some(index) => {
+                        if let Some(mut job) = self.queue.remove(index) {
+                            job.priority = priority;
+                            if let Some(persisted) = self.persistence_registry.get_mut(&id) {
+                                persisted.priority = priority;
+                            }
+                            self.enqueue(job);
+                            self.save_state();
+                        }
+                        let _ = resp.send(Ok(()));
+                    }
+                    None => { let _ = resp.send(Err("Job is not waiting in the queue".into())); }
+                }
+            },
+            JobMessage::GetCompletedEntries { video_ids, resp } => {
+                let matches = self.completed_index.iter()
+                    .filter(|e| video_ids.contains(&e.video_id))
+                    .cloned()
+                    .collect();
+                let _ = resp.send(matches);
+            },
+            JobMessage::IsUrlActive { url, resp } => {
+                let _ = resp.send(self.has_active_url(&normalize_url(&url)));
+            },
+            JobMessage::GetJobUrl { id, resp } => {
+                let url = self.jobs.get(&id).map(|j| j.url.clone())
+                    .or_else(|| self.persistence_registry.get(&id).map(|j| j.url.clone()));
+                let _ = resp.send(url);
+            },
+            JobMessage::UpdateJobMetadata { id, title, thumbnail, resp } => {
+                let queued = self.queue.iter_mut().find(|j| j.id == id);
+                let persisted = self.persistence_registry.get_mut(&id);
+                if queued.is_none() && persisted.is_none() {
+                    let _ = resp.send(Err("Job is not waiting in the queue".into()));
+                    return;
+                }
+                if let Some(job) = self.jobs.get_mut(&id) {
+                    job.title = Some(title.clone());
+                }
+                for job in queued.into_iter().chain(persisted) {
+                    job.title = Some(title.clone());
+                    if thumbnail.is_some() {
+                        job.thumbnail = thumbnail.clone();
+                    }
+                }
+                self.save_state();
+                let _ = self.app_handle.emit_all("queue-state", QueueStatePayload {
+                    pending: self.queue.iter().cloned().collect(),
+                });
+                let _ = resp.send(Ok(()));
+            },
+            JobMessage::SetJobLabel { id, label, resp } => {
+                let Some(job) = self.jobs.get_mut(&id) else {
+                    let _ = resp.send(Err("Unknown job".into()));
+                    return;
+                };
+                job.label = label.clone();
+                let queued = self.queue.iter_mut().find(|j| j.id == id);
+                let persisted = self.persistence_registry.get_mut(&id);
+                let pending = queued.is_some();
+                for job in queued.into_iter().chain(persisted) {
+                    job.label = label.clone();
+                }
+                self.save_state();
+                if pending {
+                    let _ = self.app_handle.emit_all("queue-state", QueueStatePayload {
+                        pending: self.queue.iter().cloned().collect(),
+                    });
+                }
+                let _ = resp.send(Ok(()));
+            },
+            JobMessage::SearchJobs { query, resp } => {
+                let query = query.trim().to_lowercase();
+                let matches = |text: &str| text.to_lowercase().contains(&query);
+                let results = self.jobs.values()
+                    .filter(|j| query.is_empty() || matches(&j.url) || j.title.as_deref().is_some_and(matches) || j.label.as_deref().is_some_and(matches))
+                    .map(|j| JobSearchResult { id: j.id, title: j.title.clone(), label: j.label.clone(), url: j.url.clone(), status: j.status.clone() })
+                    .collect();
+                let _ = resp.send(results);
+            },
+            JobMessage::StartQueue { ids, resp } => {
+                let selected = |id: &Uuid| ids.as_ref().is_none_or(|ids| ids.contains(id));
+                let mut released = 0;
+                for job in self.queue.iter_mut().filter(|j| j.held && selected(&j.id)) {
+                    job.held = false;
+                    released += 1;
+                }
+                for job in self.persistence_registry.values_mut().filter(|j| j.held && selected(&j.id)) {
+                    job.held = false;
+                }
+                if released > 0 {
+                    self.save_state();
+                    let _ = self.app_handle.emit_all("queue-state", QueueStatePayload {
+                        pending: self.queue.iter().cloned().collect(),
+                    });
+                    self.process_queue();
+                }
+                let _ = resp.send(released);
+            },
+            JobMessage::ResumeQueue => {
+                self.queue_halted = false;
+                // Pauses again right away if the drive is still too full
+                self.disk_space_paused = false;
+                self.process_queue();
+            },
+            JobMessage::PauseAll => {
+                self.global_paused = true;
+                let running: Vec<(Uuid, u32)> = self.jobs.values()
+                    .filter(|j| j.status == JobStatus::Downloading && !self.suspended.contains(&j.id))
+                    .filter_map(|j| j.pid.map(|pid| (j.id, pid)))
+                    .collect();
+                for (id, pid) in running {
+                    if set_process_suspended(pid, true) {
+                        self.suspended.insert(id);
+                    }
+                }
+                if self.app_handle.state::<Arc<ConfigManager>>().get_config().general.persist_global_pause {
+                    let _ = fs::write(Self::get_pause_marker_path(), "");
+                }
+                tracing::info!("Paused all downloads ({} suspended)", self.suspended.len());
+                let _ = self.app_handle.emit_all("global-paused", GlobalPausePayload { affected_jobs: self.suspended.len() as u32 });
+            },
+            JobMessage::ResumeAll => {
+                let mut resumed = 0;
+                for id in std::mem::take(&mut self.suspended) {
+                    if let Some(pid) = self.jobs.get(&id).and_then(|j| j.pid) {
+                        if set_process_suspended(pid, false) { resumed += 1; }
+                    }
+                }
+                self.global_paused = false;
+                let _ = fs::remove_file(Self::get_pause_marker_path());
+                tracing::info!("Resumed all downloads ({} continued)", resumed);
+                let _ = self.app_handle.emit_all("global-resumed", GlobalPausePayload { affected_jobs: resumed });
+                // Suspended jobs kept their slots, so only the free ones are filled
+                self.process_queue();
+            },
+            JobMessage::SetQueueDownloadPath { path, resp } => {
+                for job in self.queue.iter_mut() {
+                    job.download_path = Some(path.clone());
+                }
+                // Persisted entries (incl. failed jobs kept for retry) follow; running ones keep their folder
+                let mut updated = 0;
+                for (id, job) in self.persistence_registry.iter_mut() {
+                    let running = self.jobs.get(id).is_some_and(|j| j.status == JobStatus::Downloading);
+                    if !running {
+                        job.download_path = Some(path.clone());
+                        updated += 1;
+                    }
+                }
+                self.save_state();
+                let _ = self.app_handle.emit_all("queue-state", QueueStatePayload {
+                    pending: self.queue.iter().cloned().collect(),
+                });
+                let _ = resp.send(updated);
+            },
+            JobMessage::ClearTemp(tx) => {
+                if self.active_process_instances > 0 || !self.queue.is_empty() {
+                    let _ = tx.send(Err("Downloads are in progress; temp files are still in use.".to_string()));
+                    return;
+                }
+                let temp_path = Self::get_temp_path();
+                let before = dir_size(&temp_path);
+                // Failed jobs kept for retry resume from their partial downloads
+                self.wipe_temp_directory(!self.persistence_registry.is_empty());
+                let _ = tx.send(Ok(before.saturating_sub(dir_size(&temp_path))));
             }
         }
     }
@@ -291,19 +856,56 @@ impl JobManagerActor {
         let _ = self.app_handle.emit_all("download-progress-batch", BatchProgressPayload { updates });
     }
 
+    /// Whether a pending or downloading job already has this (normalized) URL.
+    fn has_active_url(&self, normalized: &str) -> bool {
+        self.jobs.values().any(|j| {
+            matches!(j.status, JobStatus::Pending | JobStatus::Downloading) && normalize_url(&j.url) == normalized
+        })
+    }
+
+    /// Inserts behind every queued job of equal or higher priority, keeping FIFO within a level.
+    fn enqueue(&mut self, job: QueuedJob) {
+        let index = self.queue.iter()
+            .position(|queued| queued.priority < job.priority)
+            .unwrap_or(self.queue.len());
+        self.queue.insert(index, job);
+    }
+
     fn process_queue(&mut self) {
+        if self.global_paused { return; }
+
         let config_manager = self.app_handle.state::<Arc<ConfigManager>>();
         let config = config_manager.get_config().general;
 
-        while self.active_network_jobs < config.max_concurrent_downloads 
+        // Running jobs retaking their slot go first; a halted queue only stops new jobs
+        grant_waiting_slots(&mut self.network_holders, &mut self.network_waiters, config.max_concurrent_downloads);
+        if self.queue_halted { return; }
+
+        let min_free = config.min_free_space.as_deref().filter(|s| !s.trim().is_empty()).and_then(parse_byte_size);
+        if min_free.is_none() && self.disk_space_paused {
+            self.disk_space_paused = false;
+        }
+
+        while (self.network_holders.len() as u32) < config.max_concurrent_downloads
            && self.active_process_instances < config.max_total_instances 
         {
-            if let Some(next_job) = self.queue.pop_front() {
+            let per_host = self.network_holders_per_host();
+            let host_has_room = |url: &str| {
+                config.max_concurrent_per_host == 0
+                    || per_host.get(&host_key(url)).copied().unwrap_or(0) < config.max_concurrent_per_host
+            };
+            // Held (staged) jobs and jobs whose host is at its cap keep their place but are passed over
+            let Some(index) = self.queue.iter().position(|j| !j.held && host_has_room(&j.url)) else { break; };
+            if let Some(required) = min_free {
+                let dir = job_target_dir(&self.queue[index], &config);
+                if !self.has_disk_space(dir, required) { break; }
+            }
+            if let Some(next_job) = self.queue.remove(index) {
                  if let Some(job) = self.jobs.get(&next_job.id) {
                      if job.status == JobStatus::Cancelled { continue; }
                  }
 
-                 self.active_network_jobs += 1;
+                 self.network_holders.insert(next_job.id);
                  self.active_process_instances += 1;
                  
                  let tx = self.self_sender.clone();
@@ -319,6 +921,38 @@ impl JobManagerActor {
         }
     }
 
+    /// Jobs currently holding a network slot, counted by `host_key` of their URL.
+    fn network_holders_per_host(&self) -> HashMap<String, u32> {
+        let mut counts = HashMap::new();
+        for job in self.network_holders.iter().filter_map(|id| self.jobs.get(id)) {
+            *counts.entry(host_key(&job.url)).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Whether the next job's target drive keeps `required` bytes free; toggles `disk_space_paused`
+    /// and emits the matching event when the answer changes. Unknown free space counts as enough.
+    fn has_disk_space(&mut self, dir: Option<PathBuf>, required: u64) -> bool {
+        self.last_disk_check = Instant::now();
+        let Some(dir) = dir else { return true; };
+        let Some(free) = available_space(&dir) else { return true; };
+
+        let enough = free >= required;
+        if enough == self.disk_space_paused {
+            self.disk_space_paused = !enough;
+            let event = if enough { "queue-disk-space-resumed" } else { "queue-paused-disk-full" };
+            if !enough {
+                tracing::warn!("Queue paused: {} has {} free, below the {} minimum", dir.display(), free, required);
+            }
+            let _ = self.app_handle.emit_all(event, DiskSpacePayload {
+                path: dir.to_string_lossy().to_string(),
+                free_bytes: free,
+                required_bytes: required,
+            });
+        }
+        enough
+    }
+
     fn update_native_ui(&self) {
         let active_jobs: Vec<&Job> = self.jobs.values()
             .filter(|j| j.status == JobStatus::Downloading || j.status == JobStatus::Pending)
@@ -374,19 +1008,251 @@ impl JobManagerActor {
         self.completed_session_count = 0;
     }
 
+    fn get_temp_path() -> PathBuf {
+        let home = dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
+        home.join(".multiyt-dlp").join("temp_downloads")
+    }
+
     fn clean_temp_directory(&self) {
         if !self.queue.is_empty() || !self.persistence_registry.is_empty() { return; }
+        self.wipe_temp_directory(false);
+    }
 
-        let home = dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
-        let temp_dir = home.join(".multiyt-dlp").join("temp_downloads");
-        
-        if temp_dir.exists() {
-            if let Ok(entries) = fs::read_dir(&temp_dir) {
-                for entry in entries.flatten() {
-                    if entry.path().is_dir() { let _ = fs::remove_dir_all(entry.path()); }
-                    else { let _ = fs::remove_file(entry.path()); }
+    /// Empties the temp folder; with `keep_partials`, the resumable `.part`/`.ytdl` files of jobs
+    /// kept for retry stay. Partials nothing can resume any more are removed too.
+    fn wipe_temp_directory(&self, keep_partials: bool) {
+        let temp_dir = Self::get_temp_path();
+        let keep: HashSet<PathBuf> = if keep_partials {
+            self.persistence_registry.values().map(|job| job_temp_dir(&temp_dir, &job.url)).collect()
+        } else {
+            HashSet::new()
+        };
+        wipe_temp_dir(&temp_dir, &keep);
+    }
+}
+
+/// Hands free slots to the retries in `waiters`, oldest first, while fewer than `limit` are held.
+fn grant_waiting_slots(holders: &mut HashSet<Uuid>, waiters: &mut VecDeque<(Uuid, oneshot::Sender<()>)>, limit: u32) {
+    while (holders.len() as u32) < limit {
+        let Some((id, resp)) = waiters.pop_front() else { break; };
+        // The worker stopped waiting, so the slot stays free for the next one
+        if resp.send(()).is_ok() {
+            holders.insert(id);
+        }
+    }
+}
+
+/// Removes everything in `temp_dir` except the yt-dlp partials inside the job folders in `keep`.
+fn wipe_temp_dir(temp_dir: &Path, keep: &HashSet<PathBuf>) {
+    let Ok(entries) = fs::read_dir(temp_dir) else { return; };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() && keep.contains(&path) {
+            remove_all_but_partials(&path);
+        } else if path.is_dir() {
+            let _ = fs::remove_dir_all(&path);
+        } else {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
+
+/// Deletes every file under `dir` except yt-dlp partials, then any folders left empty.
+fn remove_all_but_partials(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else { return; };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            remove_all_but_partials(&path);
+            // Fails (and is meant to) while partials remain inside
+            let _ = fs::remove_dir(&path);
+        } else if !is_partial_download(&path) {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
+
+/// Stops (or continues) a download process without killing it. Returns whether it worked.
+/// Unix uses SIGSTOP/SIGCONT; Windows suspends/resumes each of the process's threads.
+fn set_process_suspended(pid: u32, suspend: bool) -> bool {
+    #[cfg(not(windows))]
+    {
+        use nix::sys::signal::{self, Signal};
+        use nix::unistd::Pid;
+        let sig = if suspend { Signal::SIGSTOP } else { Signal::SIGCONT };
+        signal::kill(Pid::from_raw(pid as i32), sig).is_ok()
+    }
+
+    #[cfg(windows)]
+    unsafe {
+        use windows::Win32::Foundation::CloseHandle;
+        use windows::Win32::System::Diagnostics::ToolHelp::{CreateToolhelp32Snapshot, Thread32First, Thread32Next, THREADENTRY32, TH32CS_SNAPTHREAD};
+        use windows::Win32::System::Threading::{OpenThread, ResumeThread, SuspendThread, THREAD_SUSPEND_RESUME};
+
+        let Ok(snapshot) = CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0) else { return false; };
+        let mut entry = THREADENTRY32 { dwSize: std::mem::size_of::<THREADENTRY32>() as u32, ..Default::default() };
+        let mut touched = false;
+        let mut more = Thread32First(snapshot, &mut entry).as_bool();
+        while more {
+            if entry.th32OwnerProcessID == pid {
+                if let Ok(thread) = OpenThread(THREAD_SUSPEND_RESUME, false, entry.th32ThreadID) {
+                    let previous = if suspend { SuspendThread(thread) } else { ResumeThread(thread) };
+                    touched |= previous != u32::MAX;
+                    CloseHandle(thread);
                 }
             }
+            more = Thread32Next(snapshot, &mut entry).as_bool();
         }
+        CloseHandle(snapshot);
+        touched
     }
-}
\ No newline at end of file
+}
+
+/// yt-dlp's in-progress files: `<name>.part` (also `.part-FragN`) and the `.ytdl` fragment state.
+fn is_partial_download(path: &Path) -> bool {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    name.ends_with(".part") || name.contains(".part-Frag") || name.ends_with(".ytdl")
+}
+
+/// Host used for the per-host download cap; "www."/"m." variants count as the same site.
+fn host_key(url: &str) -> String {
+    let host = url_host(url);
+    host.trim_start_matches("www.").trim_start_matches("m.").to_string()
+}
+
+/// Reduces a URL to a comparable form: no scheme, "www."/"m." prefix, fragment or trailing
+/// slash, and YouTube watch/short links collapsed to the video id.
+fn normalize_url(url: &str) -> String {
+    let url = url.trim().split('#').next().unwrap_or("");
+    let without_scheme = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let lowered_host = match without_scheme.split_once('/') {
+        Some((host, path)) => format!("{}/{}", host.to_ascii_lowercase(), path),
+        None => without_scheme.to_ascii_lowercase(),
+    };
+    let stripped = lowered_host.trim_start_matches("www.").trim_start_matches("m.").trim_end_matches('/');
+
+    if let Some(id) = stripped.strip_prefix("youtu.be/") {
+        return format!("youtube:{}", id.split(['?', '&']).next().unwrap_or(id));
+    }
+    if let Some(query) = stripped.strip_prefix("youtube.com/watch?") {
+        if let Some(id) = query.split('&').find_map(|kv| kv.strip_prefix("v=")) {
+            return format!("youtube:{}", id);
+        }
+    }
+    stripped.to_string()
+}
+
+/// Playlist file names can't contain path separators or characters Windows rejects.
+fn sanitize_file_stem(name: &str) -> String {
+    let cleaned: String = name.chars()
+        .map(|c| if "<>:\"/\\|?*".contains(c) || c.is_control() { '_' } else { c })
+        .collect();
+    let cleaned = cleaned.trim().trim_end_matches('.').to_string();
+    if cleaned.is_empty() { "playlist".to_string() } else { cleaned }
+}
+
+/// Writes `<playlist title>.m3u8` next to the first file, listing the batch in playlist order.
+/// Entries under that folder are written relative to it so the folder can be moved as a whole.
+fn write_batch_playlist(mut batch: BatchTracker) {
+    batch.completed.sort_by_key(|(index, _)| *index);
+    let Some(dir) = Path::new(&batch.completed[0].1).parent().map(|p| p.to_path_buf()) else { return; };
+    let m3u_path = dir.join(format!("{}.m3u8", sanitize_file_stem(batch.title.as_deref().unwrap_or("playlist"))));
+
+    let mut content = String::from("#EXTM3U\n");
+    for (_, path) in &batch.completed {
+        let path = Path::new(path);
+        let entry = path.strip_prefix(&dir).unwrap_or(path);
+        content.push_str(&entry.to_string_lossy());
+        content.push('\n');
+    }
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = tokio::fs::write(&m3u_path, content).await {
+            tracing::warn!("Failed to write playlist file {:?}: {}", m3u_path, e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_partial_downloads() {
+        assert!(is_partial_download(Path::new("/tmp/job/video.mp4.part")));
+        assert!(is_partial_download(Path::new("/tmp/job/video.f137.mp4.part-Frag12")));
+        assert!(is_partial_download(Path::new("/tmp/job/video.mp4.ytdl")));
+        assert!(is_partial_download(Path::new("video.webm.part")));
+    }
+
+    #[test]
+    fn ignores_finished_files() {
+        assert!(!is_partial_download(Path::new("/tmp/job/video.mp4")));
+        assert!(!is_partial_download(Path::new("/tmp/job/video.info.json")));
+        assert!(!is_partial_download(Path::new("/tmp/job/party.mp4")));
+        assert!(!is_partial_download(Path::new("/tmp/job/video.part.mp4")));
+        assert!(!is_partial_download(Path::new("/tmp/job/")));
+    }
+
+
+    #[test]
+    fn clearing_temp_keeps_only_partials_of_queued_jobs() {
+        let root = std::env::temp_dir().join(format!("multiyt-dlp-temp-{}", std::process::id()));
+        let url = "https://www.youtube.com/watch?v=dQw4w9WgXcQ";
+        let kept = job_temp_dir(&root, url);
+        let orphan = job_temp_dir(&root, "https://www.youtube.com/watch?v=gone");
+        fs::create_dir_all(&kept).unwrap();
+        fs::create_dir_all(&orphan).unwrap();
+        fs::write(kept.join("video.mp4.part"), b"partial").unwrap();
+        fs::write(kept.join("video.info.json"), b"{}").unwrap();
+        fs::write(orphan.join("video.mp4.part"), b"partial").unwrap();
+        fs::write(root.join("stray.tmp"), b"").unwrap();
+
+        wipe_temp_dir(&root, &HashSet::from([kept.clone()]));
+
+        assert!(kept.join("video.mp4.part").exists());
+        assert!(!kept.join("video.info.json").exists());
+        assert!(!orphan.exists());
+        assert!(!root.join("stray.tmp").exists());
+
+        wipe_temp_dir(&root, &HashSet::new());
+        assert!(!kept.exists());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+
+    #[test]
+    fn slot_retakes_wait_for_the_download_limit() {
+        let (a, b, retry) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut holders = HashSet::from([a, b]);
+        let mut waiters = VecDeque::new();
+        let (tx, mut rx) = oneshot::channel();
+        waiters.push_back((retry, tx));
+
+        // Limit reached: the retry keeps waiting
+        grant_waiting_slots(&mut holders, &mut waiters, 2);
+        assert_eq!(holders.len(), 2);
+        assert!(rx.try_recv().is_err());
+
+        holders.remove(&a);
+        grant_waiting_slots(&mut holders, &mut waiters, 2);
+        assert_eq!(holders.len(), 2);
+        assert!(holders.contains(&retry));
+        assert!(rx.try_recv().is_ok());
+        assert!(waiters.is_empty());
+    }
+
+    #[test]
+    fn abandoned_slot_retakes_leave_the_slot_free() {
+        let (gone, next) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut holders = HashSet::new();
+        let (gone_tx, gone_rx) = oneshot::channel();
+        let (next_tx, mut next_rx) = oneshot::channel();
+        drop(gone_rx);
+        let mut waiters = VecDeque::from([(gone, gone_tx), (next, next_tx)]);
+
+        grant_waiting_slots(&mut holders, &mut waiters, 1);
+        assert_eq!(holders, HashSet::from([next]));
+        assert!(next_rx.try_recv().is_ok());
+    }
+}
@@ -3,4 +3,10 @@ pub mod manager;
 pub mod process;
 pub mod logging;
 pub mod deps;
-pub mod native;
\ No newline at end of file
+pub mod native;
+pub mod hooks;
+pub mod util;
+pub mod thumbnails;
+pub mod http_api;
+pub mod crash;
+pub mod transcode;
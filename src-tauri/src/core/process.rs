@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::process::Stdio;
 use std::sync::Arc;
 use once_cell::sync::Lazy;
@@ -5,24 +6,43 @@ use regex::Regex;
 use tauri::{AppHandle, Manager};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use std::path::{Path, PathBuf};
 use std::fs;
 use serde::Deserialize;
+use chrono::Timelike;
 
-use crate::config::ConfigManager;
-use crate::models::{DownloadFormatPreset, QueuedJob, JobMessage};
-use crate::commands::system::get_js_runtime_info;
+use crate::config::{ConfigManager, GeneralConfig, HdrPreference};
+use crate::models::{DownloadFormatPreset, ErrorCode, FormatOptions, QueuedJob, JobMessage, FilenamesSanitizedPayload, JsRuntimeMissingPayload, MusicMetadataMissingPayload, SlowDownloadWarningPayload};
+use crate::commands::system::{available_space, ensure_writable_dir, get_js_runtime_info};
+use crate::core::hooks::{delete_after_hook, run_post_hook, HookVariables};
+use crate::core::transcode::{embed_subtitle_file, ffmpeg_program, transcode_file, transcode_output_path};
+use crate::core::util::{format_bytes, format_eta, format_speed, parse_byte_size};
 
 // --- Regex Definitions ---
 static DESTINATION_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[download\]\s+Destination:\s+(?P<filename>.+)$").unwrap());
+static SUBTITLE_WRITE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[info\]\s+Writing video subtitles to:\s+(?P<filename>.+)$").unwrap());
 static ALREADY_DOWNLOADED_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[download\]\s+(?:Destination:\s+)?(?P<filename>.+?)\s+has already been downloaded").unwrap());
 static MERGER_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\[Merger\]\s+Merging formats into\s+"?(?P<filename>.+?)"?$"#).unwrap());
 static EXTRACT_AUDIO_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[ExtractAudio\]\s+Destination:\s+(?P<filename>.+)$").unwrap());
 static METADATA_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[Metadata\]\s+Adding metadata to:\s+(?P<filename>.+)$").unwrap());
 static THUMBNAIL_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[(?:Thumbnails|EmbedThumbnail)\]").unwrap());
+static COMMENTS_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^\[[^\]]+\]\s+(?:Downloading\s+(?:~?\d+\s+)?comment|Extracting comments)").unwrap());
+static WAIT_FOR_VIDEO_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[wait\]").unwrap());
+static STORYBOARD_MISSING_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)requested format is not available").unwrap());
 static FIXUP_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[(?:Fixup\w+)\]").unwrap());
 static TITLE_CLEANER_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s\[[a-zA-Z0-9_-]{11}\]\.(?:f[0-9]+\.)?[a-z0-9]+$").unwrap());
+static EXTENSION_CLEANER_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\.(?:f[0-9]+\.)?[a-z0-9]+$").unwrap());
+static UPLOAD_DATE_FIELD_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"%\(upload_date\)s").unwrap());
+static SPLIT_CHAPTERS_COUNT_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[SplitChapters\]\s+Splitting .+?;\s+(?P<count>\d+) chapters found").unwrap());
+static SPLIT_CHAPTERS_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[SplitChapters\]\s+Chapter\s+(?P<index>\d+);\s+Destination:\s+(?P<filename>.+)$").unwrap());
+static RATE_LIMITED_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)(HTTP Error 429|Too Many Requests|rate[- ]limit)").unwrap());
+static RETRY_AFTER_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)(?:retry[- ]after|try again in|wait(?:ing)?(?: for)?)[:\s]+(?P<amount>\d+)\s*(?P<unit>seconds?|secs?|s|minutes?|mins?|m|hours?|h)?\b").unwrap());
+static AGE_RESTRICTED_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)(Sign in to confirm your age|age[- ]restricted|inappropriate for some users)").unwrap());
+static BOT_CHECK_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)confirm you(?:'|’)?re not a bot").unwrap());
+static COOKIE_DECRYPT_FAILED_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)failed to decrypt").unwrap());
+static COOKIE_DB_LOCKED_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)could not copy .*cookie database").unwrap());
+static THUMBNAIL_EMBED_FAILED_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)\[EmbedThumbnail\].*(conversion failed|error opening input)").unwrap());
 static FILESYSTEM_ERROR_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)(No such file|Invalid argument|cannot be written|WinError 123|Postprocessing: Error opening input files)").unwrap());
 
 #[derive(Deserialize, Debug)]
@@ -35,33 +55,1125 @@ struct YtDlpJsonProgress {
     filename: Option<String>,
 }
 
+/// Progress line as emitted by our `--progress-template`: the progress dict plus the
+/// extractor-reported title, so the UI doesn't have to guess it from the filename.
+#[derive(Deserialize, Debug)]
+struct YtDlpProgressLine {
+    progress: YtDlpJsonProgress,
+    title: Option<String>,
+    format_id: Option<String>,
+    #[serde(default)]
+    artist: Option<String>,
+    #[serde(default)]
+    album: Option<String>,
+}
+
+const PROGRESS_TEMPLATE: &str = r#"download:{"progress":%(progress)j,"title":%(info.title)j,"format_id":%(info.format_id)j,"artist":%(info.artist)j,"album":%(info.album)j}"#;
+
+fn parse_progress_line(line: &str) -> Option<YtDlpProgressLine> {
+    if let Ok(wrapped) = serde_json::from_str::<YtDlpProgressLine>(line) {
+        return Some(wrapped);
+    }
+    // Missing fields render as a bare NA, which isn't JSON
+    let patched = line.replace(r#"":NA,"#, r#"":null,"#).replace(r#"":NA}"#, r#"":null}"#);
+    if let Ok(wrapped) = serde_json::from_str::<YtDlpProgressLine>(&patched) {
+        return Some(wrapped);
+    }
+    // Last resort: the progress dict alone
+    let inner = line.strip_prefix(r#"{"progress":"#)?;
+    let end = inner.rfind(r#","title":"#)?;
+    serde_json::from_str::<YtDlpJsonProgress>(&inner[..end]).ok()
+        .map(|progress| YtDlpProgressLine { progress, title: None, format_id: None, artist: None, album: None })
+}
+
+/// Completion message with the final file's folder, size, container (extension) and yt-dlp format id.
+fn completed_message(id: uuid::Uuid, path: &Path, format_id: Option<String>) -> JobMessage {
+    JobMessage::JobCompleted {
+        id,
+        output_path: path.to_string_lossy().to_string(),
+        download_dir: path.parent().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
+        filesize: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+        format_id,
+        container: path.extension().map(|e| e.to_string_lossy().to_ascii_lowercase()).unwrap_or_default(),
+        note: None,
+    }
+}
+
+/// Default poll interval while waiting for a premiere to go live.
+const DEFAULT_PREMIERE_CHECK_INTERVAL: u32 = 60;
+pub const MIN_PREMIERE_CHECK_INTERVAL: u32 = 10;
+/// Below this speed a YouTube download counts as throttled (~the rate seen without nsig solving).
+const THROTTLED_SPEED_BPS: f64 = 100.0 * 1024.0;
+/// How long the speed must stay below the threshold before warning.
+const THROTTLED_GRACE: std::time::Duration = std::time::Duration::from_secs(30);
+
 // --- Helpers ---
 
+/// Seconds yt-dlp waits between checks on an upcoming video (`--wait-for-video`).
+fn premiere_check_interval(job: &QueuedJob) -> u32 {
+    job.premiere_check_interval.unwrap_or(DEFAULT_PREMIERE_CHECK_INTERVAL)
+}
+
+/// Lowercased host of a URL, without credentials or port.
+pub fn url_host(url: &str) -> String {
+    let authority = url.split("://").nth(1).unwrap_or(url).split(['/', '?', '#']).next().unwrap_or("");
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    host.split(':').next().unwrap_or(host).to_ascii_lowercase()
+}
+
+fn is_youtube_url(url: &str) -> bool {
+    let host = url_host(url);
+    host == "youtu.be" || host == "youtube.com" || host.ends_with(".youtube.com")
+}
+
+/// True for Windows network paths: `\\server\share`, `//server/share` and `\\?\UNC\...`.
+/// Plain `\\?\C:\` long-path prefixes are local; elsewhere a leading `//` is just root.
+fn is_unc_path(path: &str) -> bool {
+    if !cfg!(target_os = "windows") { return false; }
+    if let Some(rest) = path.strip_prefix(r"\\?\").or_else(|| path.strip_prefix(r"\\.\")) {
+        return rest.get(..4).is_some_and(|p| p.eq_ignore_ascii_case(r"UNC\"));
+    }
+    path.starts_with(r"\\") || path.starts_with("//")
+}
+
+/// Uses backslashes for UNC paths and adds the `\\?\UNC\` prefix to long ones
+/// so copies into deep NAS folders don't trip the 260-character limit.
+fn normalize_target_dir(path: &str) -> PathBuf {
+    if !is_unc_path(path) || path.starts_with(r"\\?\") || path.starts_with(r"\\.\") {
+        return PathBuf::from(path);
+    }
+    let normalized = path.replace('/', "\\");
+    if normalized.len() > 200 {
+        return PathBuf::from(format!(r"\\?\UNC\{}", &normalized[2..]));
+    }
+    PathBuf::from(normalized)
+}
+
+/// Read/write size for cross-volume moves; each chunk is one progress step.
+const MOVE_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
 fn robust_move_file(src: &Path, dest: &Path) -> Result<(), std::io::Error> {
-    if let Err(_) = fs::rename(src, dest) {
-        fs::copy(src, dest)?;
-        fs::remove_file(src)?;
+    move_file_with_progress(src, dest, |_, _| {})
+}
+
+/// Moves are a rename when possible. Network destinations always take copy + size check +
+/// delete, since a rename across volumes fails and a half-written copy must not cost the source.
+/// The copy runs in chunks and reports (bytes copied, total) so a large file isn't silent.
+fn move_file_with_progress(src: &Path, dest: &Path, mut on_progress: impl FnMut(u64, u64)) -> Result<(), std::io::Error> {
+    // Templates with folders (e.g. artist/album/...) need the subfolders created first
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if !is_unc_path(&dest.to_string_lossy()) {
+        match fs::rename(src, dest) {
+            Ok(()) => return Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {}
+            Err(e) => tracing::debug!("Rename of {} failed ({}), copying instead", src.display(), e),
+        }
+    }
+    let copied = copy_in_chunks(src, dest, &mut on_progress)?;
+    let expected = fs::metadata(src)?.len();
+    if copied != expected || fs::metadata(dest)?.len() != expected {
+        let _ = fs::remove_file(dest);
+        return Err(std::io::Error::other(format!("copy incomplete ({} of {} bytes written)", copied, expected)));
+    }
+    fs::remove_file(src)
+}
+
+fn copy_in_chunks(src: &Path, dest: &Path, on_progress: &mut impl FnMut(u64, u64)) -> Result<u64, std::io::Error> {
+    use std::io::{Read, Write};
+
+    let total = fs::metadata(src)?.len();
+    let mut reader = fs::File::open(src)?;
+    let mut writer = fs::File::create(dest)?;
+    let mut buf = vec![0u8; MOVE_CHUNK_SIZE];
+    let mut copied = 0u64;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        writer.write_all(&buf[..n])?;
+        copied += n as u64;
+        on_progress(copied, total);
+    }
+    writer.flush()?;
+    Ok(copied)
+}
+
+/// Error text for a failed move, with a hint when the destination is an unreachable share.
+fn describe_move_error(e: &std::io::Error, dest: &Path) -> (String, Option<String>) {
+    let message = format!("Move failed: {}", e);
+    if !is_unc_path(&dest.to_string_lossy()) {
+        return (message, None);
+    }
+    // ERROR_BAD_NETPATH, ERROR_NETNAME_DELETED, ERROR_BAD_NET_NAME, ERROR_LOGON_FAILURE
+    let hint = match (e.kind(), e.raw_os_error()) {
+        (std::io::ErrorKind::PermissionDenied, _) | (_, Some(1326)) =>
+            "The network share refused access. Check that you are signed in to the share with write permission.",
+        (_, Some(53)) | (_, Some(64)) | (_, Some(67)) | (std::io::ErrorKind::NotFound, _) =>
+            "The network share is unreachable. Check that the server is online and the share name is correct.",
+        _ => "Writing to the network share failed. The file was kept in the temp folder.",
+    };
+    (message, Some(hint.to_string()))
+}
+
+/// Path of the `.info.json` sidecar yt-dlp writes next to `media` (same stem).
+pub fn info_json_path(media: &Path) -> PathBuf {
+    let stem = media.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    media.with_file_name(format!("{}.info.json", stem))
+}
+
+/// Moves per-chapter tracks from the temp dir to the target dir, keeping the
+/// subfolder yt-dlp created for them (the "album" folder).
+fn move_chapter_files(temp_dir: &Path, target_dir: &Path, files: &[String]) -> Result<(), std::io::Error> {
+    for file in files {
+        let path = Path::new(file);
+        let relative = path.strip_prefix(temp_dir).unwrap_or(path);
+        let src = temp_dir.join(relative);
+        let dest = target_dir.join(relative);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        robust_move_file(&src, &dest)?;
     }
     Ok(())
 }
 
-fn format_speed(bytes_per_sec: f64) -> String {
-    if bytes_per_sec.is_nan() || bytes_per_sec.is_infinite() { return "N/A".to_string(); }
-    const KIB: f64 = 1024.0;
-    const MIB: f64 = KIB * 1024.0;
-    const GIB: f64 = MIB * 1024.0;
-    if bytes_per_sec >= GIB { format!("{:.2} GiB/s", bytes_per_sec / GIB) }
-    else if bytes_per_sec >= MIB { format!("{:.2} MiB/s", bytes_per_sec / MIB) }
-    else if bytes_per_sec >= KIB { format!("{:.2} KiB/s", bytes_per_sec / KIB) }
-    else { format!("{:.0} B/s", bytes_per_sec) }
+/// Maps a failed run's output to a structured error code plus an actionable hint for the UI.
+fn classify_error(log_blob: &str, config: &GeneralConfig) -> (Option<ErrorCode>, Option<String>) {
+    if AGE_RESTRICTED_REGEX.is_match(log_blob) {
+        let hint = if !has_cookie_source(config) {
+            "This video is age-restricted. Set \"Cookies from browser\" in Settings to a browser where you are signed in, then retry."
+        } else {
+            "This video is age-restricted and the configured cookies were not accepted. Make sure that browser/cookie file belongs to a signed-in, age-verified account."
+        };
+        return (Some(ErrorCode::AgeRestricted), Some(hint.to_string()));
+    }
+    if COOKIE_DB_LOCKED_REGEX.is_match(log_blob) {
+        return (Some(ErrorCode::CookieDatabaseLocked), Some(COOKIE_DB_LOCKED_HINT.to_string()));
+    }
+    if COOKIE_DECRYPT_FAILED_REGEX.is_match(log_blob) && browser_cookie_source(config).is_some() {
+        return (Some(ErrorCode::CookieDecryptFailed), Some(cookie_decrypt_hint(config)));
+    }
+    if BOT_CHECK_REGEX.is_match(log_blob) {
+        let hint = if !has_cookie_source(config) {
+            "YouTube wants to confirm you're not a bot. Set \"Cookies from browser\" in Settings to a browser where you are signed in to YouTube, then retry."
+        } else {
+            "YouTube still asked to confirm you're not a bot with the configured cookies. Open YouTube in that browser to refresh the session, wait a few minutes and retry."
+        };
+        return (Some(ErrorCode::BotCheck), Some(hint.to_string()));
+    }
+    (None, None)
+}
+
+/// `--parse-metadata` pairs that set literal tag values. yt-dlp reads the FROM side as an
+/// output template, so `%` and the `:` separator in user values are escaped.
+fn build_metadata_override_args(overrides: &[(String, String)]) -> Vec<String> {
+    let mut args = Vec::new();
+    for (field, value) in overrides {
+        let literal = value.replace('%', "%%").replace(':', "\\:");
+        args.push("--parse-metadata".to_string());
+        args.push(format!("{}:%(meta_{})s", literal, field));
+    }
+    args
+}
+
+/// Writes a platform-native shortcut back to the source page next to `output`
+/// (`.url` on Windows, `.webloc` on macOS, `.desktop` elsewhere).
+fn write_source_shortcut(output: &Path, url: &str) -> Result<(), std::io::Error> {
+    let stem = output.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| "source".to_string());
+
+    #[cfg(target_os = "windows")]
+    let (extension, content) = ("url", format!("[InternetShortcut]\r\nURL={}\r\n", url));
+
+    #[cfg(target_os = "macos")]
+    let (extension, content) = {
+        let escaped = url.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+        ("webloc", format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n<plist version=\"1.0\">\n<dict>\n\t<key>URL</key>\n\t<string>{}</string>\n</dict>\n</plist>\n",
+            escaped
+        ))
+    };
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let (extension, content) = ("desktop", format!("[Desktop Entry]\nType=Link\nName={}\nURL={}\n", stem, url));
+
+    fs::write(output.with_file_name(format!("{}.{}", stem, extension)), content)
+}
+
+/// Duration of a media file in seconds via ffprobe, for transcode progress. `None` if unknown.
+async fn media_duration(bin_dir: &Path, path: &Path) -> Option<f64> {
+    let local_probe = bin_dir.join(if cfg!(windows) { "ffprobe.exe" } else { "ffprobe" });
+    let program = if local_probe.exists() { local_probe.to_string_lossy().to_string() } else { "ffprobe".to_string() };
+
+    let mut cmd = Command::new(program);
+    cmd.args(["-v", "error", "-show_entries", "format=duration", "-of", "default=noprint_wrappers=1:nokey=1"])
+        .arg(path);
+
+    #[cfg(target_os = "windows")]
+    { cmd.creation_flags(0x08000000); }
+
+    let output = cmd.output().await.ok().filter(|o| o.status.success())?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok().filter(|d: &f64| *d > 0.0)
+}
+
+/// Runs ffprobe on `path` and checks it has at least one audio/video stream and a
+/// positive duration. Returns a human-readable reason on failure.
+async fn verify_media_file(bin_dir: &Path, path: &Path) -> Result<(), String> {
+    let local_probe = bin_dir.join(if cfg!(windows) { "ffprobe.exe" } else { "ffprobe" });
+    let program = if local_probe.exists() { local_probe.to_string_lossy().to_string() } else { "ffprobe".to_string() };
+
+    let mut cmd = Command::new(program);
+    cmd.args(["-v", "error", "-show_entries", "format=duration:stream=codec_type", "-of", "json"])
+        .arg(path);
+
+    #[cfg(target_os = "windows")]
+    { cmd.creation_flags(0x08000000); }
+
+    let output = cmd.output().await.map_err(|e| format!("ffprobe unavailable: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("ffprobe rejected the file: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    let probe: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Unreadable ffprobe output: {}", e))?;
+
+    let has_media_stream = probe.get("streams")
+        .and_then(|s| s.as_array())
+        .map(|streams| streams.iter().any(|s| matches!(s.get("codec_type").and_then(|c| c.as_str()), Some("video") | Some("audio"))))
+        .unwrap_or(false);
+    if !has_media_stream {
+        return Err("No audio or video streams found".to_string());
+    }
+
+    let duration: f64 = probe.get("format")
+        .and_then(|f| f.get("duration"))
+        .and_then(|d| d.as_str())
+        .and_then(|d| d.parse().ok())
+        .unwrap_or(0.0);
+    if duration <= 0.0 {
+        return Err("File reports zero duration".to_string());
+    }
+
+    Ok(())
+}
+
+/// Downloads the best storyboard (`sb0`, falling back to smaller ones) as `<stem>.storyboard.<ext>`
+/// into `target_dir`. `Ok(None)` when the site offers no storyboard for this video.
+async fn download_storyboard(job: &QueuedJob, config: &GeneralConfig, cookies: &CookieArgs, bin_dir: &Path, temp_dir: &Path, target_dir: &Path, stem: &str) -> Result<Option<PathBuf>, String> {
+    let mut cmd = new_configured_ytdlp_command(bin_dir, config, job.proxy.as_deref(), cookies);
+    cmd.current_dir(temp_dir)
+        .arg("-f").arg("sb0/sb1/sb2/sb3")
+        .arg("-o").arg(format!("{}.storyboard.%(ext)s", stem.replace('%', "%%")))
+        .arg("--no-simulate")
+        .arg("--print").arg("after_move:filepath")
+        .arg("--no-warnings")
+        .arg("--no-playlist")
+        .arg(&job.url);
+
+    let output = cmd.output().await.map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if STORYBOARD_MISSING_REGEX.is_match(&stderr) {
+            return Ok(None);
+        }
+        return Err(stderr.trim().to_string());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let Some(written) = stdout.lines().map(str::trim).rfind(|l| !l.is_empty()) else { return Ok(None); };
+    let src = temp_dir.join(written);
+    let Some(name) = src.file_name() else { return Ok(None); };
+    let dest = target_dir.join(name);
+    robust_move_file(&src, &dest).map_err(|e| e.to_string())?;
+    Ok(Some(dest))
+}
+
+/// Output path of `job` relative to the download folder, from a `--print filename` dry run with
+/// the job's template and format selection. `None` when yt-dlp can't tell (e.g. extraction fails).
+async fn predict_output_filename(bin_dir: &Path, job: &QueuedJob, config: &GeneralConfig, cookies: &CookieArgs, url: &str) -> Option<String> {
+    let mut cmd = new_configured_ytdlp_command(bin_dir, config, job.proxy.as_deref(), cookies);
+    cmd.arg(url)
+        .arg("-o").arg(apply_upload_date_format(&job.filename_template, config.upload_date_format.as_deref()))
+        .args(["--simulate", "--no-playlist", "--no-warnings", "--windows-filenames", "--encoding", "utf-8", "--print", "filename"])
+        .args(build_format_args(&FormatOptions::from(job), config));
+    if job.restrict_filenames {
+        cmd.arg("--restrict-filenames").arg("--trim-filenames").arg("200");
+    }
+    let output = cmd.output().await.ok().filter(|o| o.status.success())?;
+    String::from_utf8_lossy(&output.stdout).lines().map(str::trim).rfind(|l| !l.is_empty()).map(str::to_string)
+}
+
+/// The job's finished file if it is already in `target_dir`. `predicted` carries the extension
+/// yt-dlp downloads, so audio conversion and transcoding are mapped to their final names.
+fn existing_output(target_dir: &Path, predicted: &str, job: &QueuedJob) -> Option<PathBuf> {
+    let base = target_dir.join(predicted);
+    let extensions: &[&str] = match job.format_preset {
+        DownloadFormatPreset::AudioMp3 => &["mp3"],
+        DownloadFormatPreset::AudioFlac => &["flac"],
+        DownloadFormatPreset::AudioM4a => &["m4a"],
+        // -x keeps the source codec, which the dry run can't know
+        DownloadFormatPreset::AudioBest => &["opus", "m4a", "mp3", "ogg", "aac", "flac", "wav"],
+        _ => &[],
+    };
+    let mut candidates: Vec<PathBuf> = if extensions.is_empty() {
+        vec![base]
+    } else {
+        extensions.iter().map(|ext| base.with_extension(ext)).collect()
+    };
+    if let Some(profile) = &job.transcode_profile {
+        candidates = candidates.iter().map(|c| transcode_output_path(c, profile)).collect();
+    }
+    candidates.into_iter().find(|c| c.is_file())
+}
+
+/// Creates a yt-dlp command using the managed binary when present, with the bin dir
+/// prepended to PATH (so ffmpeg/deno resolve) and the JS runtime wired in.
+pub fn new_ytdlp_command(bin_dir: &Path) -> Command {
+    let mut yt_dlp_cmd = "yt-dlp".to_string();
+    let local_exe = bin_dir.join(if cfg!(windows) { "yt-dlp.exe" } else { "yt-dlp" });
+    if local_exe.exists() { yt_dlp_cmd = local_exe.to_string_lossy().to_string(); }
+
+    let mut cmd = Command::new(yt_dlp_cmd);
+
+    if let Ok(current_path) = std::env::var("PATH") {
+        let new_path = format!("{}{}{}", bin_dir.to_string_lossy(), if cfg!(windows) { ";" } else { ":" }, current_path);
+        cmd.env("PATH", new_path);
+    } else {
+        cmd.env("PATH", bin_dir.to_string_lossy().to_string());
+    }
+
+    cmd.env("PYTHONUTF8", "1");
+    cmd.env("PYTHONIOENCODING", "utf-8");
+
+    #[cfg(target_os = "windows")]
+    { cmd.creation_flags(0x08000000); }
+
+    if let Some((name, path)) = get_js_runtime_info(&bin_dir.to_path_buf()) {
+        cmd.arg("--js-runtimes").arg(format!("{}:{}", name, path));
+    }
+
+    cmd
+}
+
+/// The job's own proxy, else the global one.
+pub fn effective_proxy<'a>(job_proxy: Option<&'a str>, config: &'a GeneralConfig) -> Option<&'a str> {
+    job_proxy.or(config.proxy.as_deref()).filter(|p| !p.trim().is_empty())
+}
+
+/// `new_ytdlp_command` plus what every request to the site needs: the proxy (see `effective_proxy`)
+/// and the cookies. Keep `cookies` alive until the command has finished.
+pub fn new_configured_ytdlp_command(bin_dir: &Path, config: &GeneralConfig, job_proxy: Option<&str>, cookies: &CookieArgs) -> Command {
+    let mut cmd = new_ytdlp_command(bin_dir);
+    if let Some(proxy) = effective_proxy(job_proxy, config) {
+        cmd.arg("--proxy").arg(proxy);
+    }
+    cmd.args(&cookies.args);
+    cmd
+}
+
+/// Resume flags, passed explicitly so a user config can't turn resuming off: a retry picks up
+/// the `.part` left in temp. With `redownload_existing`, partial files are discarded instead.
+fn resume_args(redownload_existing: bool) -> &'static [&'static str] {
+    if redownload_existing {
+        &["--no-continue", "--force-overwrites"]
+    } else {
+        &["--continue"]
+    }
 }
 
-fn format_eta(seconds: u64) -> String {
-    let h = seconds / 3600;
-    let m = (seconds % 3600) / 60;
-    let s = seconds % 60;
-    if h > 0 { format!("{:02}:{:02}:{:02}", h, m, s) }
-    else { format!("{:02}:{:02}", m, s) }
+/// Cookie arguments for one yt-dlp invocation. When the cookies come from `cookies_text`
+/// they are written to a private temp file, which is deleted when this value is dropped,
+/// so keep it alive until the command has finished.
+pub struct CookieArgs {
+    pub args: Vec<String>,
+    temp_file: Option<PathBuf>,
+}
+
+impl Drop for CookieArgs {
+    fn drop(&mut self) {
+        if let Some(path) = &self.temp_file {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// True if any cookie source is configured. Precedence: file > text > browser.
+pub fn has_cookie_source(config: &GeneralConfig) -> bool {
+    let set = |v: &Option<String>| v.as_deref().is_some_and(|s| !s.trim().is_empty());
+    set(&config.cookies_path)
+        || set(&config.cookies_text)
+        || config.cookies_from_browser.as_deref().is_some_and(|b| !b.trim().is_empty() && b != "none")
+}
+
+fn write_cookie_text(text: &str) -> std::io::Result<PathBuf> {
+    use std::io::Write;
+    let path = std::env::temp_dir().join(format!("multiyt-dlp-cookies-{}.txt", uuid::Uuid::new_v4()));
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path)?;
+    file.write_all(text.as_bytes())?;
+    if !text.ends_with('\n') { file.write_all(b"\n")?; }
+    Ok(path)
+}
+
+const COOKIE_DB_LOCKED_HINT: &str = "The browser's cookie database is locked (Chrome-based browsers hold it while running). Close the browser completely and retry, or export cookies to a file instead.";
+
+/// Linux secret stores yt-dlp can read the Chromium cookie key from.
+pub const COOKIE_KEYRINGS: &[&str] = &["basictext", "gnomekeyring", "kwallet", "kwallet5", "kwallet6"];
+
+fn cookie_decrypt_hint(config: &GeneralConfig) -> String {
+    if cfg!(target_os = "linux") && config.cookies_browser_keyring.as_deref().is_none_or(|k| k.trim().is_empty()) {
+        format!("The browser's cookies couldn't be decrypted. On Linux, set the cookie keyring in Settings to the one your desktop uses (usually gnomekeyring on GNOME, kwallet6 on KDE Plasma 6; one of {}).", COOKIE_KEYRINGS.join(", "))
+    } else {
+        "The browser's cookies couldn't be decrypted. Check the configured keyring, or export cookies to a file instead.".to_string()
+    }
+}
+
+/// `--cookies-from-browser` value: `BROWSER[+KEYRING][:PROFILE]`, with the keyring slotted in
+/// before any profile already given with the browser.
+fn browser_cookie_arg(browser: &str, keyring: Option<&str>) -> String {
+    let browser = browser.trim();
+    match keyring.map(str::trim).filter(|k| !k.is_empty()) {
+        Some(keyring) => match browser.split_once(':') {
+            Some((name, profile)) => format!("{}+{}:{}", name, keyring, profile),
+            None => format!("{}+{}", browser, keyring),
+        },
+        None => browser.to_string(),
+    }
+}
+
+/// Attempts at exporting browser cookies while the database is locked, and the pause between them.
+const COOKIE_EXPORT_ATTEMPTS: u32 = 3;
+const COOKIE_EXPORT_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// The browser whose cookies are used, when browser extraction is the active source.
+fn browser_cookie_source(config: &GeneralConfig) -> Option<&str> {
+    let set = |v: &Option<String>| v.as_deref().is_some_and(|s| !s.trim().is_empty());
+    if set(&config.cookies_path) || set(&config.cookies_text) { return None; }
+    config.cookies_from_browser.as_deref().filter(|b| !b.trim().is_empty() && *b != "none")
+}
+
+/// Reads the browser's cookies into a private temp file (yt-dlp saves its jar on exit) so the
+/// job uses a snapshot taken just now. A locked cookie database is retried a few times.
+/// Errors carry a user-facing hint.
+async fn export_browser_cookies(bin_dir: &Path, browser: &str, config: &GeneralConfig, job_proxy: Option<&str>, url: &str) -> Result<CookieArgs, (String, Option<ErrorCode>, Option<String>)> {
+    let path = std::env::temp_dir().join(format!("multiyt-dlp-cookies-{}.txt", uuid::Uuid::new_v4()));
+    let cookies = CookieArgs {
+        args: vec!["--cookies".to_string(), path.to_string_lossy().to_string()],
+        temp_file: Some(path.clone()),
+    };
+
+    for attempt in 1..=COOKIE_EXPORT_ATTEMPTS {
+        // `cookies` names the jar yt-dlp saves the browser's cookies to
+        let output = new_configured_ytdlp_command(bin_dir, config, job_proxy, &cookies)
+            .arg("--cookies-from-browser").arg(browser_cookie_arg(browser, config.cookies_browser_keyring.as_deref()))
+            .arg("--simulate").arg("--skip-download").arg("--no-playlist")
+            .arg("--quiet").arg("--no-warnings")
+            .arg(url)
+            .output()
+            .await
+            .map_err(|e| (format!("Cookie refresh failed: {}", e), None, None))?;
+
+        if path.exists() {
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let _ = fs::set_permissions(&path, fs::Permissions::from_mode(0o600));
+            }
+            return Ok(cookies);
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        if COOKIE_DECRYPT_FAILED_REGEX.is_match(&stderr) {
+            return Err((format!("Cookie refresh failed: {}", stderr.trim()), Some(ErrorCode::CookieDecryptFailed), Some(cookie_decrypt_hint(config))));
+        }
+        if !COOKIE_DB_LOCKED_REGEX.is_match(&stderr) {
+            return Err((format!("Cookie refresh failed: {}", stderr.trim()), None, None));
+        }
+        if attempt < COOKIE_EXPORT_ATTEMPTS {
+            tracing::warn!("Cookie database for {} is locked; retrying ({}/{})", browser, attempt, COOKIE_EXPORT_ATTEMPTS);
+            tokio::time::sleep(COOKIE_EXPORT_RETRY_DELAY).await;
+        }
+    }
+    Err(("Could not read cookies: the browser's cookie database is locked.".to_string(), Some(ErrorCode::CookieDatabaseLocked), Some(COOKIE_DB_LOCKED_HINT.to_string())))
+}
+
+/// Cookie arguments from the general config. A cookie file takes precedence over pasted
+/// cookie text, which takes precedence over browser extraction.
+pub fn build_cookie_args(config: &GeneralConfig) -> CookieArgs {
+    let mut cookies = CookieArgs { args: Vec::new(), temp_file: None };
+    if let Some(cookie_path) = config.cookies_path.as_deref().filter(|p| !p.trim().is_empty()) {
+        cookies.args.push("--cookies".to_string());
+        cookies.args.push(cookie_path.to_string());
+    } else if let Some(text) = config.cookies_text.as_deref().filter(|t| !t.trim().is_empty()) {
+        match write_cookie_text(text) {
+            Ok(path) => {
+                cookies.args.push("--cookies".to_string());
+                cookies.args.push(path.to_string_lossy().to_string());
+                cookies.temp_file = Some(path);
+            }
+            Err(e) => tracing::warn!("Failed to write cookie text to a temp file: {}", e),
+        }
+    } else if let Some(browser) = &config.cookies_from_browser {
+        if !browser.trim().is_empty() && browser != "none" {
+            cookies.args.push("--cookies-from-browser".to_string());
+            cookies.args.push(browser_cookie_arg(browser, config.cookies_browser_keyring.as_deref()));
+        }
+    }
+    cookies
+}
+
+/// Accepts only Netscape-format cookie jars: the standard header plus tab-separated rows.
+pub fn validate_cookie_text(text: &str) -> Result<(), String> {
+    let mut lines = text.lines().map(str::trim_end).filter(|l| !l.trim().is_empty());
+    let header = lines.next().unwrap_or("");
+    if !header.starts_with("# Netscape HTTP Cookie File") && !header.starts_with("# HTTP Cookie File") {
+        return Err("Cookie text must start with the \"# Netscape HTTP Cookie File\" header.".to_string());
+    }
+    let mut rows = 0;
+    for line in lines {
+        // "#HttpOnly_" prefixed rows are cookies; other '#' lines are comments
+        if line.starts_with('#') && !line.starts_with("#HttpOnly_") { continue; }
+        if line.split('\t').count() != 7 {
+            return Err(format!("Malformed cookie line (expected 7 tab-separated fields): {}", line));
+        }
+        rows += 1;
+    }
+    if rows == 0 {
+        return Err("Cookie text contains no cookies.".to_string());
+    }
+    Ok(())
+}
+
+/// Sort fields accepted by yt-dlp's `-S` (see "Sorting Formats" in its README).
+const FORMAT_SORT_FIELDS: &[&str] = &[
+    "hasvid", "hasaud", "ie_pref", "lang", "quality", "source", "proto", "vcodec", "acodec",
+    "codec", "vext", "aext", "ext", "filesize", "fs_approx", "size", "height", "width", "res",
+    "fps", "hdr", "channels", "tbr", "vbr", "abr", "br", "asr", "id",
+];
+
+/// Checks a `-S` string such as "res:1080,+fps,vcodec:h264" for unknown field names.
+pub fn validate_format_sort(sort: &str) -> Result<(), String> {
+    for token in sort.split(',').map(str::trim) {
+        let field = token.trim_start_matches('+')
+            .split([':', '~'])
+            .next()
+            .unwrap_or("");
+        if !FORMAT_SORT_FIELDS.contains(&field) {
+            return Err(format!("Unknown format sort field '{}'.", token));
+        }
+    }
+    Ok(())
+}
+
+const PROXY_SCHEMES: &[&str] = &["http", "https", "socks4", "socks4a", "socks5", "socks5h"];
+
+/// Accepts `scheme://[user:pass@]host:port` with a scheme yt-dlp understands.
+pub fn validate_proxy_url(proxy: &str) -> Result<(), String> {
+    let (scheme, rest) = proxy.split_once("://")
+        .ok_or_else(|| format!("Proxy '{}' must include a scheme (e.g. socks5://host:1080).", redact_proxy_credentials(proxy)))?;
+    if !PROXY_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str()) {
+        return Err(format!("Unsupported proxy scheme '{}'. Use one of: {}.", scheme, PROXY_SCHEMES.join(", ")));
+    }
+    let host = rest.rsplit_once('@').map(|(_, h)| h).unwrap_or(rest);
+    if host.trim_end_matches('/').is_empty() {
+        return Err("Proxy is missing a host.".to_string());
+    }
+    Ok(())
+}
+
+/// Masks the `user:pass@` part of a proxy URL so it can be logged.
+pub fn redact_proxy_credentials(proxy: &str) -> String {
+    match proxy.split_once("://") {
+        Some((scheme, rest)) => match rest.rsplit_once('@') {
+            Some((_, host)) => format!("{}://***@{}", scheme, host),
+            None => proxy.to_string(),
+        },
+        None => proxy.to_string(),
+    }
+}
+
+/// Rewrites plain `%(upload_date)s` fields as `%(upload_date>FMT)s` so yt-dlp formats the date.
+/// Fields that already carry a format or default are left alone.
+pub fn apply_upload_date_format(template: &str, date_format: Option<&str>) -> String {
+    match date_format.map(str::trim).filter(|f| !f.is_empty()) {
+        Some(fmt) => UPLOAD_DATE_FIELD_REGEX
+            .replace_all(template, regex::NoExpand(&format!("%(upload_date>{})s", fmt)))
+            .into_owned(),
+        None => template.to_string(),
+    }
+}
+
+/// The format is spliced into an output template field, so it must not close the field early.
+pub fn validate_upload_date_format(fmt: &str) -> Result<(), String> {
+    if fmt.contains(')') || fmt.contains('/') || fmt.contains('\\') {
+        return Err(format!("Invalid upload date format '{}'.", fmt));
+    }
+    if !fmt.contains('%') {
+        return Err(format!("Upload date format '{}' has no strftime fields (e.g. %Y-%m-%d).", fmt));
+    }
+    Ok(())
+}
+
+/// Checked with chrono's parser; path components may not climb out of or replace the target folder.
+pub fn validate_date_subfolder_format(fmt: &str) -> Result<(), String> {
+    let fmt = fmt.trim();
+    if !fmt.contains('%') {
+        return Err(format!("Date subfolder format '{}' has no strftime fields (e.g. %Y/%m).", fmt));
+    }
+    if chrono::format::StrftimeItems::new(fmt).any(|item| matches!(item, chrono::format::Item::Error)) {
+        return Err(format!("Invalid date subfolder format '{}'.", fmt));
+    }
+    if fmt.starts_with(['/', '\\']) || fmt.contains(':') || fmt.split(['/', '\\']).any(|part| part.trim() == "..") {
+        return Err(format!("Date subfolder format '{}' must be a relative path.", fmt));
+    }
+    Ok(())
+}
+
+/// `target` plus the download date rendered through `date_subfolder_format`, if set.
+fn date_subfolder(target: PathBuf, format: Option<&str>, now: chrono::DateTime<chrono::Local>) -> PathBuf {
+    match format.map(str::trim).filter(|f| !f.is_empty() && validate_date_subfolder_format(f).is_ok()) {
+        Some(fmt) => target.join(now.format(fmt).to_string()),
+        None => target,
+    }
+}
+
+/// Derives a display title from an output filename. The `[id]` suffix is only stripped when
+/// the template actually produces one; custom templates just lose the extension.
+fn clean_title_from_filename(fname: &str, template: &str) -> String {
+    if template.contains("[%(id)s]") {
+        let cleaned = TITLE_CLEANER_REGEX.replace(fname, "");
+        if cleaned.len() != fname.len() {
+            return cleaned.into_owned();
+        }
+    }
+    EXTENSION_CLEANER_REGEX.replace(fname, "").into_owned()
+}
+
+/// Height asked for by the job's resolution ("720p"); `None` for "best".
+fn requested_height(opts: &FormatOptions) -> Option<u32> {
+    if opts.video_resolution == "best" { return None; }
+    let number_part: String = opts.video_resolution.chars().filter(|c| c.is_numeric()).collect();
+    number_part.parse().ok()
+}
+
+/// Height limit for video formats: the job's resolution, clamped by `global_max_height`.
+fn effective_max_height(opts: &FormatOptions, config: &GeneralConfig) -> Option<u32> {
+    match (requested_height(opts), config.global_max_height.filter(|h| *h > 0)) {
+        (Some(requested), Some(cap)) => Some(requested.min(cap)),
+        (requested, cap) => requested.or(cap),
+    }
+}
+
+/// Plain-language reading of what `build_format_args` asks yt-dlp for, e.g. "Best video up to
+/// 720p plus best audio, falling back to the best combined format; preferring H.264".
+pub fn describe_format_args(opts: &FormatOptions, config: &GeneralConfig) -> String {
+    if opts.preset == DownloadFormatPreset::SubtitlesOnly {
+        return format!("No video or audio; only subtitles in: {}", config.subtitle_langs.trim());
+    }
+    let max_height = effective_max_height(opts, config);
+    let up_to = max_height.map(|h| format!(" up to {}p", h)).unwrap_or_default();
+    let audio_lang = opts.audio_lang.as_deref().map(str::trim).filter(|l| !l.is_empty());
+    let audio_track = match audio_lang {
+        Some(lang) => format!("best audio in '{}' (else the default track)", lang),
+        None => "best audio".to_string(),
+    };
+    let lossy = |codec: &str| match opts.audio_bitrate {
+        Some(kbps) => format!("{}, converted to {} at {} kbps", capitalize_first(&audio_track), codec, kbps),
+        None => format!("{}, converted to {} at the best VBR quality", capitalize_first(&audio_track), codec),
+    };
+
+    let mut parts = vec![if config.prefer_progressive && !opts.preset.is_audio() {
+        let container = match opts.preset {
+            DownloadFormatPreset::Best | DownloadFormatPreset::BestMp4 => " MP4",
+            DownloadFormatPreset::BestWebm => " WebM",
+            _ => "",
+        };
+        format!("Best single-file{} format (video and audio in one stream){}, no merging; hosts often cap these at a lower quality", container, up_to)
+    } else {
+        match opts.preset {
+            DownloadFormatPreset::Best => format!("Best video{} plus {}, falling back to the best combined format", up_to, audio_track),
+            DownloadFormatPreset::BestMp4 => format!("Best video{} plus {}, merged into MP4", up_to, audio_track),
+            DownloadFormatPreset::BestMkv => format!("Best video{} plus {}, merged into MKV", up_to, audio_track),
+            DownloadFormatPreset::BestWebm => format!("Best video{} plus {}, merged into WebM", up_to, audio_track),
+            DownloadFormatPreset::AudioBest => format!("{}, kept in its original format", capitalize_first(&audio_track)),
+            DownloadFormatPreset::AudioMp3 => lossy("MP3"),
+            DownloadFormatPreset::AudioFlac => format!("{}, converted to FLAC (lossless)", capitalize_first(&audio_track)),
+            DownloadFormatPreset::AudioM4a => lossy("M4A"),
+            DownloadFormatPreset::SubtitlesOnly => unreachable!(),
+        }
+    }];
+
+    let global_cap = config.global_max_height.filter(|h| *h > 0);
+    if !opts.preset.is_audio() && global_cap.is_some_and(|cap| requested_height(opts).is_none_or(|h| h > cap)) {
+        parts.push("limited by the global maximum height".to_string());
+    }
+    if normalizes_loudness(&opts.preset, config) {
+        parts.push("loudness normalized".to_string());
+    }
+    if let Some(target) = opts.target_filesize.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        parts.push(format!("preferring the largest format not above {} and skipping anything bigger", target));
+    }
+    if !opts.preset.is_audio() {
+        match config.hdr_preference {
+            HdrPreference::Keep => {}
+            HdrPreference::PreferSdr => parts.push("preferring SDR over HDR".to_string()),
+            HdrPreference::PreferHdr => parts.push("preferring HDR".to_string()),
+        }
+    }
+    if let Some(sort) = config.format_sort.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        parts.push(describe_format_sort(sort));
+    }
+    parts.join("; ")
+}
+
+fn capitalize_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Reads the common `format_sort` fields (codecs, fps, containers); anything else is quoted as is.
+fn describe_format_sort(sort: &str) -> String {
+    let codec_name = |codec: &str| match codec.to_lowercase().as_str() {
+        "h264" | "avc" | "avc1" => "H.264".to_string(),
+        "h265" | "hevc" => "H.265".to_string(),
+        "av01" | "av1" => "AV1".to_string(),
+        "vp9" | "vp09" => "VP9".to_string(),
+        "opus" => "Opus".to_string(),
+        "aac" | "mp4a" => "AAC".to_string(),
+        other => other.to_string(),
+    };
+    let described: Vec<String> = sort.split(',').map(str::trim).filter(|f| !f.is_empty()).map(|field| {
+        let field = field.trim_start_matches('+');
+        match field.split_once(':') {
+            Some(("vcodec", codec)) => format!("{} video", codec_name(codec)),
+            Some(("acodec", codec)) => format!("{} audio", codec_name(codec)),
+            Some(("ext", ext)) => format!("{} files", ext.to_uppercase()),
+            Some(("fps", fps)) => format!("up to {} fps", fps),
+            Some(("res", res)) => format!("resolution near {}p", res),
+            None if field == "fps" => "higher frame rates".to_string(),
+            None if field == "res" => "higher resolutions".to_string(),
+            _ => format!("'{}'", field),
+        }
+    }).collect();
+    format!("then preferring {}", described.join(", then "))
+}
+
+/// Format selection (`-f`), merge container and audio extraction arguments for a preset.
+/// The per-job resolution is clamped by `global_max_height` so the cap applies to every preset.
+/// A `target_filesize` adds `-S filesize:<target>` (largest format not above the target)
+/// within the preset's selector, with `--max-filesize` as a hard stop if nothing fits.
+pub fn build_format_args(opts: &FormatOptions, config: &GeneralConfig) -> Vec<String> {
+    let max_height = effective_max_height(opts, config);
+
+    let height_filter = match max_height {
+        Some(h) => format!("[height<={}]", h),
+        None => String::new(),
+    };
+
+    let audio_lang = opts.audio_lang.as_deref().map(str::trim).filter(|l| !l.is_empty());
+
+    // With a language, try the matching track first and fall back to the default one
+    let video_audio = match audio_lang {
+        Some(lang) => format!("bestvideo{h}+bestaudio[language={l}]/bestvideo{h}+bestaudio", h = height_filter, l = lang),
+        None => format!("bestvideo{}+bestaudio", height_filter),
+    };
+    let audio_only = match audio_lang {
+        Some(lang) => format!("bestaudio[language={}]/bestaudio/best", lang),
+        None => "bestaudio/best".to_string(),
+    };
+
+    // "0" is yt-dlp's best VBR quality; an explicit bitrate is passed as e.g. "128K"
+    let lossy_quality = match opts.audio_bitrate {
+        Some(kbps) => format!("{}K", kbps),
+        None => "0".to_string(),
+    };
+
+    if opts.preset == DownloadFormatPreset::SubtitlesOnly {
+        return vec!["--skip-download".into(), "--write-subs".into(), "--sub-langs".into(), config.subtitle_langs.trim().to_string()];
+    }
+
+    let mut args: Vec<String> = if config.prefer_progressive && !opts.preset.is_audio() {
+        // Single-file formats only: no merge step and no ffmpeg, but hosts usually cap
+        // progressive streams at a lower quality (often 720p) than separate video/audio.
+        let ext_filter = match opts.preset {
+            DownloadFormatPreset::Best | DownloadFormatPreset::BestMp4 => "[ext=mp4]",
+            DownloadFormatPreset::BestWebm => "[ext=webm]",
+            _ => "",
+        };
+        let mut selector = format!("best{e}{h}/best{h}", e = ext_filter, h = height_filter);
+        if let Some(lang) = audio_lang {
+            selector = format!("best{e}[language={l}]{h}/{rest}", e = ext_filter, l = lang, h = height_filter, rest = selector);
+        }
+        vec!["-f".into(), selector]
+    } else {
+        match opts.preset {
+            DownloadFormatPreset::Best => {
+                if !height_filter.is_empty() || audio_lang.is_some() {
+                    vec!["-f".into(), format!("{}/best{}", video_audio, height_filter)]
+                } else {
+                    vec![]
+                }
+            }
+            DownloadFormatPreset::BestMp4 => vec!["-f".into(), video_audio, "--merge-output-format".into(), "mp4".into()],
+            DownloadFormatPreset::BestMkv => vec!["-f".into(), video_audio, "--merge-output-format".into(), "mkv".into()],
+            DownloadFormatPreset::BestWebm => vec!["-f".into(), video_audio, "--merge-output-format".into(), "webm".into()],
+            DownloadFormatPreset::AudioBest => vec!["-x".into(), "-f".into(), audio_only.clone()],
+            DownloadFormatPreset::AudioMp3 => vec!["-x".into(), "--audio-format".into(), "mp3".into(), "--audio-quality".into(), lossy_quality.clone()],
+            // Lossless: a bitrate makes no sense here
+            DownloadFormatPreset::AudioFlac => vec!["-x".into(), "--audio-format".into(), "flac".into(), "--audio-quality".into(), "0".into()],
+            DownloadFormatPreset::AudioM4a => vec!["-x".into(), "--audio-format".into(), "m4a".into(), "--audio-quality".into(), lossy_quality.clone()],
+            // Returned early above
+            DownloadFormatPreset::SubtitlesOnly => vec![],
+        }
+    };
+
+    if audio_lang.is_some() && !matches!(opts.preset, DownloadFormatPreset::AudioBest) && opts.preset.is_audio() {
+        args.push("-f".into());
+        args.push(audio_only);
+    }
+
+    // Scoped to ExtractAudio's output so merging/thumbnail embedding stay untouched
+    if normalizes_loudness(&opts.preset, config) {
+        args.push("--postprocessor-args".into());
+        args.push(format!("ExtractAudio+ffmpeg_o:-af {}", LOUDNORM_FILTER));
+    }
+
+    // Precedence: the -f filters above (resolution cap, language) decide which formats are
+    // eligible; -S only orders them. A size target sorts first, then the HDR preference, then
+    // the user's format_sort, and --max-filesize still rejects anything over the target outright.
+    let target = opts.target_filesize.as_deref().map(str::trim).filter(|t| !t.is_empty());
+    let mut sort_fields: Vec<String> = Vec::new();
+    if let Some(target) = target {
+        sort_fields.push(format!("filesize:{}", target));
+    }
+    if !opts.preset.is_audio() {
+        match config.hdr_preference {
+            HdrPreference::Keep => {}
+            // "hdr:SDR" ranks nothing above SDR, so HDR only wins when no SDR format exists
+            HdrPreference::PreferSdr => sort_fields.push("hdr:SDR".into()),
+            HdrPreference::PreferHdr => sort_fields.push("hdr".into()),
+        }
+    }
+    if let Some(sort) = config.format_sort.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        sort_fields.push(sort.to_string());
+    }
+    if !sort_fields.is_empty() {
+        args.push("-S".into());
+        args.push(sort_fields.join(","));
+    }
+    if let Some(target) = target {
+        args.push("--max-filesize".into());
+        args.push(target.to_string());
+    }
+
+    args
+}
+
+/// Share of the reported size a finished file may fall short by before counting as truncated;
+/// merging/remuxing and embedded metadata shift sizes by a few percent either way.
+const SIZE_MISMATCH_TOLERANCE: f64 = 0.10;
+
+/// Whether `actual` is well below the `expected` total of the downloaded streams.
+/// An unknown (zero) expectation never mismatches.
+fn is_size_mismatch(expected: u64, actual: u64) -> bool {
+    expected > 0 && (actual as f64) < expected as f64 * (1.0 - SIZE_MISMATCH_TOLERANCE)
+}
+
+/// Phases that run locally (ffmpeg etc.) after the download, letting the next job use the network.
+fn is_postprocessing_phase(phase: &str) -> bool {
+    ["Merging", "Extracting", "Normalizing", "Splitting", "Fixing", "Embedding", "Writing Metadata", "Transcoding"].iter().any(|p| phase.starts_with(p))
+}
+
+/// Pause before the single retry of a bot-check failure; the challenge is often transient.
+const BOT_CHECK_RETRY_DELAY: u64 = 20;
+
+/// Retries of a rate-limited (HTTP 429) job before giving up.
+const RATE_LIMIT_MAX_RETRIES: u32 = 3;
+/// First wait when the error names no duration; doubles on each further retry.
+const RATE_LIMIT_DEFAULT_BACKOFF: u64 = 60;
+/// Upper bound for any wait, including one the server asked for.
+const RATE_LIMIT_MAX_BACKOFF: u64 = 600;
+/// How often the countdown of a rate-limit wait is refreshed in the UI.
+const RATE_LIMIT_PROGRESS_INTERVAL: u64 = 30;
+
+/// Seconds to wait before retrying a rate-limited run: a duration named in the output
+/// ("retry after 120", "try again in 5 minutes") if any, else exponential backoff; capped.
+/// `None` when the failure wasn't rate limiting.
+fn rate_limit_backoff(log_blob: &str, attempt: u32) -> Option<u64> {
+    if !RATE_LIMITED_REGEX.is_match(log_blob) {
+        return None;
+    }
+    let hinted = RETRY_AFTER_REGEX.captures_iter(log_blob).last().and_then(|caps| {
+        let amount: u64 = caps["amount"].parse().ok()?;
+        let unit = caps.name("unit").map(|u| u.as_str().to_ascii_lowercase()).unwrap_or_default();
+        Some(match unit.chars().next() {
+            Some('m') => amount * 60,
+            Some('h') => amount * 3600,
+            _ => amount,
+        })
+    });
+    let backoff = hinted.unwrap_or(RATE_LIMIT_DEFAULT_BACKOFF << attempt.min(4));
+    Some(backoff.clamp(1, RATE_LIMIT_MAX_BACKOFF))
+}
+
+/// Podcast-style loudness target: -16 LUFS integrated, -1.5 dBTP peak, 11 LU range.
+const LOUDNORM_FILTER: &str = "loudnorm=I=-16:TP=-1.5:LRA=11";
+
+/// Whether `normalize_audio` applies: presets that always re-encode (a stream copy can't be filtered).
+fn normalizes_loudness(preset: &DownloadFormatPreset, config: &GeneralConfig) -> bool {
+    config.normalize_audio && matches!(preset, DownloadFormatPreset::AudioMp3 | DownloadFormatPreset::AudioFlac)
+}
+
+/// Targets accepted for `thumbnail_format` (`--convert-thumbnails`).
+pub const THUMBNAIL_FORMATS: &[&str] = &["jpg", "png", "webp"];
+
+/// `--convert-thumbnails` value for an embedded thumbnail: forced jpg after a failed embed, else
+/// the user's format, else jpg for MP4/MKV output (WebP embedding there depends on the ffmpeg build).
+fn thumbnail_conversion(preset: &DownloadFormatPreset, config: &GeneralConfig, force_jpg: bool) -> Option<String> {
+    if force_jpg {
+        return Some("jpg".into());
+    }
+    if let Some(format) = config.thumbnail_format.as_deref().map(str::trim).filter(|f| !f.is_empty()) {
+        return Some(format.to_string());
+    }
+    matches!(preset, DownloadFormatPreset::Best | DownloadFormatPreset::BestMp4 | DownloadFormatPreset::BestMkv)
+        .then(|| "jpg".into())
+}
+
+/// Keeps `preferred` while it has at least the pool threshold free; otherwise the first pool
+/// folder that does. If none qualifies (or space can't be read) the preferred folder is kept.
+fn pick_target_dir(preferred: PathBuf, config: &GeneralConfig) -> PathBuf {
+    let pool: Vec<&str> = config.download_path_pool.iter().map(|p| p.trim()).filter(|p| !p.is_empty()).collect();
+    if pool.is_empty() { return preferred; }
+    let Some(min_free) = parse_byte_size(&config.download_path_pool_min_free) else { return preferred; };
+
+    let has_room = |dir: &Path| available_space(dir).is_some_and(|free| free >= min_free);
+    if has_room(&preferred) { return preferred; }
+    match pool.into_iter().map(normalize_target_dir).find(|dir| has_room(dir)) {
+        Some(dir) => {
+            tracing::info!("{} is low on space; using {}", preferred.display(), dir.display());
+            dir
+        }
+        None => preferred,
+    }
+}
+
+/// Folder a job downloads into: its own path, else the OS media folder, then diverted to a
+/// pool folder if low on space. `None` if no folder can be determined.
+pub fn job_target_dir(job: &QueuedJob, config: &GeneralConfig) -> Option<PathBuf> {
+    let preferred = match job.download_path.as_deref() {
+        Some(path) => normalize_target_dir(path),
+        None => default_target_dir(&job.format_preset)?,
+    };
+    Some(pick_target_dir(preferred, config))
+}
+
+/// OS media folder for the preset (Music for audio, Videos for video), falling back to Downloads.
+fn default_target_dir(preset: &DownloadFormatPreset) -> Option<PathBuf> {
+    let media_dir = if preset.is_audio() { tauri::api::path::audio_dir() } else { tauri::api::path::video_dir() };
+    media_dir.or_else(tauri::api::path::download_dir)
+}
+
+/// Temp folder for `url`'s download attempts under `temp_root`. Keyed by URL, not job, so any
+/// retry (a manual one queues a new job) finds the `.part` files of earlier attempts, and
+/// clearing temp can tell which partials still belong to a job that may be retried.
+pub fn job_temp_dir(temp_root: &Path, url: &str) -> PathBuf {
+    // FNV-1a: stable across builds, unlike `DefaultHasher`, so partials survive an update
+    let hash = url.trim().bytes().fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+    temp_root.join(format!("{:016x}", hash))
+}
+
+/// yt-dlp arguments for one attempt at `job`, after the config location, ffmpeg and cookies.
+/// Built per attempt, so a retry picks up the rate window active at `hour`.
+fn download_args(job: &QueuedJob, config: &GeneralConfig, hour: u32, force_jpg_thumbnail: bool) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        job.url.clone(),
+        "-o".into(), apply_upload_date_format(&job.filename_template, config.upload_date_format.as_deref()),
+        "--no-playlist".into(),
+        "--no-simulate".into(),
+        "--newline".into(),
+        "--windows-filenames".into(),
+        "--encoding".into(), "utf-8".into(),
+        "--progress-template".into(), PROGRESS_TEMPLATE.into(),
+    ];
+
+    if let Some(limit) = config.effective_rate_limit(hour) {
+        args.extend(["--limit-rate".into(), limit]);
+    }
+
+    args.extend(["--fragment-retries".into(), config.fragment_retries.to_string()]);
+    if let Some(size) = config.buffer_size.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        args.extend(["--buffer-size".into(), size.to_string()]);
+    }
+    // Fragment concurrency covers HLS/DASH; chunking is what speeds up a single progressive file
+    if config.concurrent_fragments > 1 {
+        args.extend(["--concurrent-fragments".into(), config.concurrent_fragments.to_string()]);
+    }
+    if let Some(size) = config.http_chunk_size.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        args.extend(["--http-chunk-size".into(), size.to_string()]);
+    }
+
+    if job.restrict_filenames {
+        args.extend(["--restrict-filenames".into(), "--trim-filenames".into(), "200".into()]);
+    }
+
+    if job.split_audio_chapters && job.format_preset.is_audio() {
+        // Tracks land in a folder named after the source, one file per chapter.
+        args.extend(["--split-chapters".into(), "-o".into(), "chapter:%(title)s/%(section_number)02d - %(section_title)s.%(ext)s".into()]);
+    }
+
+    // Nothing to embed into when only subtitles are fetched
+    let subtitles_only = job.format_preset == DownloadFormatPreset::SubtitlesOnly;
+    if job.embed_metadata && !subtitles_only {
+        args.push("--embed-metadata".into());
+        args.extend(build_metadata_override_args(&job.metadata_overrides));
+    }
+    if job.embed_thumbnail && !subtitles_only {
+        args.push("--embed-thumbnail".into());
+        // Music libraries get jpg covers, the one format every tag reader and player shows
+        if let Some(format) = thumbnail_conversion(&job.format_preset, config, force_jpg_thumbnail || job.music_library) {
+            args.extend(["--convert-thumbnails".into(), format]);
+        }
+    }
+    // Comments are stored inside the info.json, so they imply writing it
+    if config.write_info_json || job.write_comments { args.push("--write-info-json".into()); }
+    if job.write_comments { args.push("--write-comments".into()); }
+    if job.live_from_start { args.push("--live-from-start".into()); }
+    if job.wait_for_premiere {
+        args.extend(["--wait-for-video".into(), premiere_check_interval(job).to_string()]);
+    }
+    // The job's own proxy wins over the global one
+    if let Some(proxy) = effective_proxy(job.proxy.as_deref(), config) {
+        tracing::debug!("Job {} using proxy {}", job.id, redact_proxy_credentials(proxy));
+        args.extend(["--proxy".into(), proxy.to_string()]);
+    }
+    args.extend(resume_args(config.redownload_existing).iter().map(|a| a.to_string()));
+
+    args.extend(build_format_args(&FormatOptions::from(job), config));
+    args
+}
+
+/// Frees the job's download slot while it does local work (post-processing, transcoding) or
+/// waits out a rate limit.
+/// `released` makes the next retry take the slot back with `NetworkStarted`.
+async fn release_network(tx_actor: &mpsc::Sender<JobMessage>, id: uuid::Uuid, released: &mut bool) {
+    if !*released {
+        let _ = tx_actor.send(JobMessage::NetworkFinished { id }).await;
+        *released = true;
+    }
 }
 
 // --- Main Process Logic ---
@@ -82,116 +1194,140 @@ pub async fn run_download_process(
         eta: "Calculating...".to_string(),
         filename: None,
         phase: "Initializing Process...".to_string(),
+        speed_bps: None,
     }).await;
 
     let config_manager = app_handle.state::<Arc<ConfigManager>>();
+    let mut verify_retried = false;
+    let mut force_jpg_thumbnail = false;
+    let mut rate_limit_retries = 0;
+    let mut bot_check_retried = false;
+    let mut js_runtime_checked = false;
+    let mut music_tags_checked = false;
+    let mut existing_checked = false;
+    // Set once NetworkFinished was sent; a retry has to take the network slot back
+    let mut network_released = false;
 
     loop {
+        if network_released {
+            let (tx, rx) = oneshot::channel();
+            let _ = tx_actor.send(JobMessage::NetworkStarted { id: job_id, resp: tx }).await;
+            // Waits for a free slot; dropped unanswered when the job is cancelled meanwhile
+            if rx.await.is_err() {
+                let _ = tx_actor.send(JobMessage::WorkerFinished { id: job_id }).await;
+                return;
+            }
+            network_released = false;
+        }
         // Refresh config on retry
         let general_config = config_manager.get_config().general;
 
         let app_dir = app_handle.path_resolver().app_data_dir().unwrap();
         let bin_dir = app_dir.join("bin");
         
-        let target_dir = if let Some(ref path) = job_data.download_path {
-            PathBuf::from(path)
-        } else {
-            match tauri::api::path::download_dir() {
-                Some(path) => path,
-                None => {
-                    let _ = tx_actor.send(JobMessage::JobError { id: job_id, error: "Missing download dir".into() }).await;
-                    let _ = tx_actor.send(JobMessage::WorkerFinished).await;
-                    return;
-                }
-            }
+        let Some(target_dir) = job_target_dir(&job_data, &general_config) else {
+            let _ = tx_actor.send(JobMessage::JobError { id: job_id, error: "Missing download dir".into(), code: None, hint: None }).await;
+            let _ = tx_actor.send(JobMessage::WorkerFinished { id: job_id }).await;
+            return;
         };
-        
-        if !target_dir.exists() { let _ = std::fs::create_dir_all(&target_dir); }
+        let target_dir = date_subfolder(target_dir, general_config.date_subfolder_format.as_deref(), chrono::Local::now());
         let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-        let temp_dir = home.join(".multiyt-dlp").join("temp_downloads");
-        if !temp_dir.exists() { let _ = std::fs::create_dir_all(&temp_dir); }
+        let temp_dir = job_temp_dir(&home.join(".multiyt-dlp").join("temp_downloads"), &url);
 
-        let mut yt_dlp_cmd = "yt-dlp".to_string();
-        let local_exe = bin_dir.join(if cfg!(windows) { "yt-dlp.exe" } else { "yt-dlp" });
-        if local_exe.exists() { yt_dlp_cmd = local_exe.to_string_lossy().to_string(); }
+        // Fail now rather than at the final move, after a possibly long download
+        for dir in [&target_dir, &temp_dir] {
+            if let Err(e) = ensure_writable_dir(dir) {
+                let _ = tx_actor.send(JobMessage::JobError {
+                    id: job_id,
+                    error: e,
+                    code: Some(ErrorCode::TargetNotWritable),
+                    hint: Some("Choose a folder you have write access to, or fix its permissions, then retry.".into()),
+                }).await;
+                let _ = tx_actor.send(JobMessage::WorkerFinished { id: job_id }).await;
+                return;
+            }
+        }
 
-        let mut cmd = Command::new(yt_dlp_cmd);
-        
-        if let Ok(current_path) = std::env::var("PATH") {
-            let new_path = format!("{}{}{}", bin_dir.to_string_lossy(), if cfg!(windows) { ";" } else { ":" }, current_path);
-            cmd.env("PATH", new_path);
-        } else {
-            cmd.env("PATH", bin_dir.to_string_lossy().to_string());
+        if !js_runtime_checked && is_youtube_url(&url) && get_js_runtime_info(&bin_dir).is_none() {
+            let blocked = general_config.require_js_runtime;
+            let message = "No JavaScript runtime found. YouTube downloads will be slow or fail without one; install the portable Deno runtime under Dependencies.".to_string();
+            tracing::warn!("Job {}: {}", job_id, message);
+            let _ = app_handle.emit_all("js-runtime-missing", JsRuntimeMissingPayload { job_id, blocked, message: message.clone() });
+            if blocked {
+                let _ = tx_actor.send(JobMessage::JobError {
+                    id: job_id,
+                    error: "No JavaScript runtime is installed".into(),
+                    code: Some(ErrorCode::JsRuntimeMissing),
+                    hint: Some(message),
+                }).await;
+                let _ = tx_actor.send(JobMessage::WorkerFinished { id: job_id }).await;
+                return;
+            }
         }
-        
-        cmd.env("PYTHONUTF8", "1");
-        cmd.env("PYTHONIOENCODING", "utf-8");
+        // Warn once per job, not again on every retry
+        js_runtime_checked = true;
+
+        let mut cmd = new_ytdlp_command(&bin_dir);
         cmd.current_dir(&temp_dir);
 
-        if let Some((name, path)) = get_js_runtime_info(&bin_dir) {
-            cmd.arg("--js-runtimes").arg(format!("{}:{}", name, path));
+        // The user's config is loaded first; options given later on the command line
+        // (output template, progress template, format selection) take precedence over it.
+        if let Some(config_path) = &general_config.ytdlp_config_path {
+            if !config_path.trim().is_empty() { cmd.arg("--config-location").arg(config_path); }
         }
-
-        if let Some(cookie_path) = &general_config.cookies_path {
-            if !cookie_path.trim().is_empty() { cmd.arg("--cookies").arg(cookie_path); }
-        } else if let Some(browser) = &general_config.cookies_from_browser {
-            if !browser.trim().is_empty() && browser != "none" { cmd.arg("--cookies-from-browser").arg(browser); }
+        if let Some(ffmpeg) = general_config.ffmpeg_path.as_deref().filter(|p| !p.trim().is_empty()) {
+            cmd.arg("--ffmpeg-location").arg(ffmpeg.trim());
         }
+        let cookies = match browser_cookie_source(&general_config).filter(|_| general_config.refresh_browser_cookies) {
+            Some(browser) => {
+                let _ = tx_actor.send(JobMessage::UpdateProgress {
+                    id: job_id, percentage: 0.0, speed: "N/A".to_string(), eta: "N/A".to_string(), filename: None,
+                    phase: "Refreshing browser cookies".to_string(), speed_bps: None,
+                }).await;
+                match export_browser_cookies(&bin_dir, browser, &general_config, job_data.proxy.as_deref(), &url).await {
+                    Ok(cookies) => cookies,
+                    Err((error, code, hint)) => {
+                        let _ = tx_actor.send(JobMessage::JobError { id: job_id, error, code, hint }).await;
+                        break;
+                    }
+                }
+            }
+            None => build_cookie_args(&general_config),
+        };
+        cmd.args(&cookies.args);
 
-        cmd.arg(&url)
-            .arg("-o").arg(&job_data.filename_template) 
-            .arg("--no-playlist")
-            .arg("--no-simulate") 
-            .arg("--newline")
-            .arg("--windows-filenames")
-            .arg("--encoding").arg("utf-8")
-            .arg("--progress-template").arg("download:%(progress)j");
+        let check_existing = general_config.skip_existing && !general_config.redownload_existing
+            && job_data.format_preset != DownloadFormatPreset::SubtitlesOnly && !job_data.live_from_start;
+        // Here rather than at queue time: the date subfolder and pooled path are only known now
+        if check_existing && !existing_checked {
+            existing_checked = true;
+            let _ = tx_actor.send(JobMessage::UpdateProgress {
+                id: job_id, percentage: 0.0, speed: "N/A".to_string(), eta: "N/A".to_string(), filename: None,
+                phase: "Checking for existing file".to_string(), speed_bps: None,
+            }).await;
+            let predicted = predict_output_filename(&bin_dir, &job_data, &general_config, &cookies, &url).await;
+            if let Some(existing) = predicted.as_deref().and_then(|name| existing_output(&target_dir, name, &job_data)) {
+                tracing::info!("Skipping job {}: {} already exists", job_id, existing.display());
+                let _ = tx_actor.send(JobMessage::JobSkipped {
+                    id: job_id,
+                    reason: format!("Already exists, skipped: {}", existing.display()),
+                    output_path: existing.to_string_lossy().to_string(),
+                }).await;
+                break;
+            }
+        }
 
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
-
-        #[cfg(target_os = "windows")]
-        { cmd.creation_flags(0x08000000); }
-
-        if job_data.restrict_filenames {
-            cmd.arg("--restrict-filenames").arg("--trim-filenames").arg("200");
-        }
-
-        if job_data.embed_metadata { cmd.arg("--embed-metadata"); }
-        if job_data.embed_thumbnail { cmd.arg("--embed-thumbnail"); }
-
-        let height_filter = if job_data.video_resolution != "best" {
-            let number_part: String = job_data.video_resolution.chars().filter(|c| c.is_numeric()).collect();
-            if !number_part.is_empty() { format!("[height<={}]", number_part) } else { String::new() }
-        } else { String::new() };
-
-        match job_data.format_preset {
-            DownloadFormatPreset::Best => {
-                if !height_filter.is_empty() { cmd.arg("-f").arg(format!("bestvideo{}+bestaudio/best{}", height_filter, height_filter)); }
-            }
-            DownloadFormatPreset::BestMp4 => {
-                cmd.arg("-f").arg(format!("bestvideo{}+bestaudio", height_filter));
-                cmd.args(["--merge-output-format", "mp4"]);
-            }
-            DownloadFormatPreset::BestMkv => {
-                cmd.arg("-f").arg(format!("bestvideo{}+bestaudio", height_filter));
-                cmd.args(["--merge-output-format", "mkv"]);
-            }
-            DownloadFormatPreset::BestWebm => {
-                cmd.arg("-f").arg(format!("bestvideo{}+bestaudio", height_filter));
-                cmd.args(["--merge-output-format", "webm"]);
-            }
-            DownloadFormatPreset::AudioBest => { cmd.arg("-x").args(["-f", "bestaudio/best"]); }
-            DownloadFormatPreset::AudioMp3 => { cmd.arg("-x").args(["--audio-format", "mp3", "--audio-quality", "0"]); }
-            DownloadFormatPreset::AudioFlac => { cmd.arg("-x").args(["--audio-format", "flac", "--audio-quality", "0"]); }
-            DownloadFormatPreset::AudioM4a => { cmd.arg("-x").args(["--audio-format", "m4a", "--audio-quality", "0"]); }
-        }
+        cmd.args(download_args(&job_data, &general_config, chrono::Local::now().hour(), force_jpg_thumbnail));
+        // Nothing to embed into when only subtitles are fetched
+        let subtitles_only = job_data.format_preset == DownloadFormatPreset::SubtitlesOnly;
 
         let mut child = match cmd.spawn() {
             Ok(child) => child,
             Err(e) => {
-                let _ = tx_actor.send(JobMessage::JobError { id: job_id, error: e.to_string() }).await;
-                let _ = tx_actor.send(JobMessage::WorkerFinished).await;
+                let _ = tx_actor.send(JobMessage::JobError { id: job_id, error: e.to_string(), code: None, hint: None }).await;
+                let _ = tx_actor.send(JobMessage::WorkerFinished { id: job_id }).await;
                 return;
             }
         };
@@ -203,7 +1339,7 @@ pub async fn run_download_process(
         if job_data.restrict_filenames {
             let _ = tx_actor.send(JobMessage::UpdateProgress {
                 id: job_id, percentage: 0.0, speed: "Retrying...".to_string(), eta: "--".to_string(), filename: None,
-                phase: "Sanitizing Filenames (Retry)".to_string(),
+                phase: "Sanitizing Filenames (Retry)".to_string(), speed_bps: None,
             }).await;
         }
 
@@ -227,20 +1363,37 @@ pub async fn run_download_process(
         drop(tx);
 
         let mut state_clean_title: Option<String> = None;
+        // Set once yt-dlp reports the real title; filename-derived guesses no longer override it
+        let mut state_title_from_info = false;
         let mut state_final_filename: Option<String> = None; 
+        let mut state_already_downloaded = false;
+        let mut state_format_id: Option<String> = None;
         let mut state_percentage: f32 = 0.0;
         let mut state_phase: String = "Initializing".to_string();
+        let mut state_chapter_total: Option<u32> = None;
+        let mut state_chapter_files: Vec<String> = Vec::new();
+        let mut state_subtitle_files: Vec<String> = Vec::new();
+        // Exact total_bytes per downloaded stream (keyed by file) and whether audio was re-encoded, for verify_size
+        let mut state_stream_sizes: HashMap<String, u64> = HashMap::new();
+        let mut state_audio_converted = false;
+        // Start of the current below-threshold stretch; the warning fires at most once per job
+        let watch_throttling = is_youtube_url(&url);
+        let mut slow_since: Option<std::time::Instant> = None;
+        let mut throttle_warned = false;
         let mut captured_logs = Vec::new();
         
         let extract_filename_from_path = |path_str: &str| -> Option<String> {
             Path::new(path_str).file_name().map(|os| os.to_string_lossy().to_string())
         };
         let extract_clean_title = |path_str: &str| -> Option<String> {
-             if let Some(fname) = extract_filename_from_path(path_str) {
-                let cleaned = TITLE_CLEANER_REGEX.replace(&fname, "");
-                return Some(cleaned.to_string());
-             }
-             None
+             extract_filename_from_path(path_str).map(|fname| clean_title_from_filename(&fname, &job_data.filename_template))
+        };
+        // Output path relative to the temp folder, keeping any subfolders the template created
+        let extract_output_path = |path_str: &str| -> Option<String> {
+            let path = Path::new(path_str);
+            let relative = path.strip_prefix(&temp_dir).unwrap_or(path);
+            if relative.is_absolute() { return extract_filename_from_path(path_str); }
+            relative.file_name().map(|_| relative.to_string_lossy().to_string())
         };
 
         while let Some(line) = rx.recv().await {
@@ -251,29 +1404,94 @@ pub async fn run_download_process(
 
             let mut emit_update = false;
             let mut speed_str = "N/A".to_string();
+            let mut speed_bps: Option<f64> = None;
             let mut eta_str = "N/A".to_string();
 
-            if let Ok(progress_json) = serde_json::from_str::<YtDlpJsonProgress>(trimmed) {
+            if let Some(line) = parse_progress_line(trimmed) {
+                let progress_json = line.progress;
+                if let Some(title) = line.title.filter(|t| !t.trim().is_empty()) {
+                    state_clean_title = Some(title);
+                    state_title_from_info = true;
+                }
+                if job_data.music_library && !music_tags_checked {
+                    music_tags_checked = true;
+                    let mut missing = Vec::new();
+                    if line.artist.as_deref().is_none_or(|a| a.trim().is_empty()) { missing.push("artist".to_string()); }
+                    if line.album.as_deref().is_none_or(|a| a.trim().is_empty()) { missing.push("album".to_string()); }
+                    if !missing.is_empty() {
+                        let folders: Vec<String> = missing.iter().map(|field| match field.as_str() {
+                            "artist" => "\"Unknown Artist\"".to_string(),
+                            _ => "\"Unknown Album\"".to_string(),
+                        }).collect();
+                        let message = format!("The source has no {} tag, so this track will be filed under {}.", missing.join(" or "), folders.join("/"));
+                        tracing::warn!("Job {}: {}", job_id, message);
+                        let _ = app_handle.emit_all("music-metadata-missing", MusicMetadataMissingPayload { job_id, missing, message });
+                    }
+                }
+                // Merged downloads report each part's id in turn; join them as yt-dlp does ("137+140")
+                if let Some(part) = line.format_id.filter(|f| !f.is_empty()) {
+                    let known = state_format_id.as_deref().is_some_and(|ids| ids.split('+').any(|id| id == part));
+                    if !known {
+                        state_format_id = Some(match state_format_id.take() {
+                            Some(ids) => format!("{}+{}", ids, part),
+                            None => part,
+                        });
+                    }
+                }
+                if let (Some(file), Some(total)) = (progress_json.filename.as_ref(), progress_json.total_bytes) {
+                    state_stream_sizes.insert(file.clone(), total);
+                }
+                // Live recordings have no known total; report bytes captured instead of a percentage
+                let mut live_downloaded: Option<u64> = None;
                 if let Some(d) = progress_json.downloaded_bytes {
-                     let t = progress_json.total_bytes.or(progress_json.total_bytes_estimate);
-                     if let Some(total) = t { state_percentage = (d as f32 / total as f32) * 100.0; }
+                     let t = progress_json.total_bytes.or(progress_json.total_bytes_estimate).filter(|t| *t > 0);
+                     match t {
+                         Some(total) => state_percentage = ((d as f32 / total as f32) * 100.0).min(100.0),
+                         None if job_data.live_from_start => live_downloaded = Some(d),
+                         None => {}
+                     }
+                }
+                if let Some(s) = progress_json.speed { speed_str = format_speed(s, general_config.speed_units); speed_bps = Some(s); }
+                if watch_throttling && !throttle_warned && live_downloaded.is_none() {
+                    match progress_json.speed {
+                        Some(s) if s < THROTTLED_SPEED_BPS => {
+                            let since = *slow_since.get_or_insert_with(std::time::Instant::now);
+                            if since.elapsed() >= THROTTLED_GRACE {
+                                throttle_warned = true;
+                                let js_runtime = get_js_runtime_info(&bin_dir).map(|(name, _)| name);
+                                let message = match &js_runtime {
+                                    Some(name) => format!("YouTube download is unusually slow ({}). The {} runtime may be failing to solve YouTube's signature challenge; check it under Dependencies.", format_speed(s, general_config.speed_units), name),
+                                    None => format!("YouTube download is unusually slow ({}). No JavaScript runtime was found, which YouTube needs for full-speed downloads; install one under Dependencies.", format_speed(s, general_config.speed_units)),
+                                };
+                                tracing::warn!("Job {}: {}", job_id, message);
+                                let _ = app_handle.emit_all("slow-download-warning", SlowDownloadWarningPayload {
+                                    job_id, speed_bps: s, js_runtime, message,
+                                });
+                            }
+                        }
+                        Some(_) => slow_since = None,
+                        None => {}
+                    }
                 }
-                if let Some(s) = progress_json.speed { speed_str = format_speed(s); }
                 if let Some(e) = progress_json.eta { eta_str = format_eta(e); }
                 if let Some(f) = progress_json.filename {
-                     if let Some(n) = extract_filename_from_path(&f) {
+                     if let Some(n) = extract_output_path(&f) {
                          if state_clean_title.is_none() { state_clean_title = extract_clean_title(&n); }
                          state_final_filename = Some(n);
                      }
                 }
                 
-                if !state_phase.contains("Merging") && !state_phase.contains("Extracting") && !state_phase.contains("Writing") && !state_phase.contains("Embedding") {
+                if let Some(d) = live_downloaded {
+                    state_phase = format!("Recording live (downloaded {})", format_bytes(d as f64));
+                    state_percentage = 0.0;
+                    eta_str = "Live".to_string();
+                } else if !state_phase.contains("Merging") && !state_phase.contains("Extracting") && !state_phase.contains("Normalizing") && !state_phase.contains("Writing") && !state_phase.contains("Embedding") {
                     state_phase = "Downloading".to_string();
                 }
                 emit_update = true;
             } else {
                 if let Some(caps) = METADATA_REGEX.captures(trimmed) {
-                    if let Some(f) = caps.name("filename") { state_final_filename = extract_filename_from_path(f.as_str()); }
+                    if let Some(f) = caps.name("filename") { state_final_filename = extract_output_path(f.as_str()); }
                     state_phase = "Writing Metadata".to_string();
                     state_percentage = 99.0;
                     emit_update = true;
@@ -285,8 +1503,8 @@ pub async fn run_download_process(
                 }
                 else if let Some(caps) = MERGER_REGEX.captures(trimmed) {
                     if let Some(f) = caps.name("filename") {
-                        state_final_filename = extract_filename_from_path(f.as_str());
-                        state_clean_title = extract_clean_title(f.as_str()).or(state_clean_title);
+                        state_final_filename = extract_output_path(f.as_str());
+                        if !state_title_from_info { state_clean_title = extract_clean_title(f.as_str()).or(state_clean_title); }
                     }
                     state_phase = "Merging Formats".to_string();
                     state_percentage = 100.0;
@@ -294,23 +1512,64 @@ pub async fn run_download_process(
                     emit_update = true;
                 }
                 else if let Some(caps) = EXTRACT_AUDIO_REGEX.captures(trimmed) {
+                    state_audio_converted = true;
                     if let Some(f) = caps.name("filename") {
-                        state_final_filename = extract_filename_from_path(f.as_str());
-                        state_clean_title = extract_clean_title(f.as_str()).or(state_clean_title);
+                        state_final_filename = extract_output_path(f.as_str());
+                        if !state_title_from_info { state_clean_title = extract_clean_title(f.as_str()).or(state_clean_title); }
                     }
-                    state_phase = "Extracting Audio".to_string();
+                    state_phase = if normalizes_loudness(&job_data.format_preset, &general_config) {
+                        "Normalizing Loudness".to_string()
+                    } else {
+                        "Extracting Audio".to_string()
+                    };
+                    state_percentage = 100.0;
+                    eta_str = "Done".to_string();
+                    emit_update = true;
+                }
+                else if let Some(caps) = SPLIT_CHAPTERS_COUNT_REGEX.captures(trimmed) {
+                    state_chapter_total = caps.name("count").and_then(|c| c.as_str().parse().ok());
+                    state_phase = "Splitting into tracks".to_string();
+                    emit_update = true;
+                }
+                else if let Some(caps) = SPLIT_CHAPTERS_REGEX.captures(trimmed) {
+                    if let Some(f) = caps.name("filename") { state_chapter_files.push(f.as_str().to_string()); }
+                    let index: u32 = caps.name("index").and_then(|i| i.as_str().parse().ok()).unwrap_or(0);
+                    state_phase = match state_chapter_total {
+                        Some(total) => format!("Splitting into tracks ({}/{})", index, total),
+                        None => format!("Splitting into tracks ({})", index),
+                    };
                     state_percentage = 100.0;
                     eta_str = "Done".to_string();
                     emit_update = true;
                 }
+                else if let Some(caps) = SUBTITLE_WRITE_REGEX.captures(trimmed) {
+                    if let Some(f) = caps.name("filename").and_then(|f| extract_filename_from_path(f.as_str())) {
+                        state_subtitle_files.push(f);
+                    }
+                    state_phase = "Writing Subtitles".to_string();
+                    state_percentage = 100.0;
+                    emit_update = true;
+                }
+                else if job_data.write_comments && COMMENTS_REGEX.is_match(trimmed) {
+                    if state_phase != "Fetching comments" {
+                        state_phase = "Fetching comments".to_string();
+                        emit_update = true;
+                    }
+                }
+                else if job_data.wait_for_premiere && WAIT_FOR_VIDEO_REGEX.is_match(trimmed) {
+                    state_phase = format!("Waiting for premiere (checking every {}s)", premiere_check_interval(&job_data));
+                    eta_str = "Upcoming".to_string();
+                    emit_update = true;
+                }
                 else if FIXUP_REGEX.is_match(trimmed) {
                     state_phase = "Fixing Container".to_string();
                     emit_update = true;
                 }
                 else if let Some(caps) = ALREADY_DOWNLOADED_REGEX.captures(trimmed) {
+                    state_already_downloaded = true;
                     if let Some(f) = caps.name("filename") {
-                        state_final_filename = extract_filename_from_path(f.as_str());
-                        state_clean_title = extract_clean_title(f.as_str()).or(state_clean_title);
+                        state_final_filename = extract_output_path(f.as_str());
+                        if !state_title_from_info { state_clean_title = extract_clean_title(f.as_str()).or(state_clean_title); }
                     }
                     state_phase = "Finished".to_string();
                     state_percentage = 100.0;
@@ -321,7 +1580,7 @@ pub async fn run_download_process(
                     if let Some(f) = caps.name("filename") {
                         let full_path_str = f.as_str();
                         if state_clean_title.is_none() { state_clean_title = extract_clean_title(full_path_str); }
-                        state_final_filename = extract_filename_from_path(full_path_str);
+                        state_final_filename = extract_output_path(full_path_str);
                         state_phase = "Downloading".to_string();
                         emit_update = true;
                     }
@@ -335,53 +1594,502 @@ pub async fn run_download_process(
                     speed: speed_str,
                     eta: eta_str,
                     filename: state_clean_title.clone(),
-                    phase: state_phase.clone()
+                    phase: state_phase.clone(),
+                    speed_bps,
                 }).await;
             }
+            if is_postprocessing_phase(&state_phase) {
+                release_network(&tx_actor, job_id, &mut network_released).await;
+            }
         }
 
         let status = child.wait().await.expect("Child process error");
 
-        if status.success() {
+        if status.success() && subtitles_only {
+            // No media is written; the subtitle files are the output
+            if state_subtitle_files.is_empty() {
+                let _ = tx_actor.send(JobMessage::JobCompleted {
+                    id: job_id,
+                    output_path: String::new(),
+                    download_dir: target_dir.to_string_lossy().to_string(),
+                    filesize: 0,
+                    format_id: None,
+                    container: String::new(),
+                    note: Some(format!("No subtitles available for \"{}\"", general_config.subtitle_langs.trim())),
+                }).await;
+                break;
+            }
+            let mut moved = Vec::new();
+            for name in &state_subtitle_files {
+                let dest_path = target_dir.join(name);
+                if let Err(e) = robust_move_file(&temp_dir.join(name), &dest_path) {
+                    let (error, hint) = describe_move_error(&e, &dest_path);
+                    let _ = tx_actor.send(JobMessage::JobError { id: job_id, error, code: None, hint }).await;
+                    break;
+                }
+                moved.push(dest_path);
+            }
+            if moved.len() == state_subtitle_files.len() {
+                let _ = tx_actor.send(completed_message(job_id, &moved[0], None)).await;
+            }
+            break;
+        } else if status.success() {
             if let Some(filename) = state_final_filename {
                 let src_path = temp_dir.join(&filename);
                 let dest_path = target_dir.join(&filename);
+
+                // Nothing new was written; point at the copy already in the target folder
+                if state_already_downloaded && dest_path.exists() {
+                    let _ = tx_actor.send(completed_message(job_id, &dest_path, state_format_id)).await;
+                    break;
+                }
                 
                 if src_path.exists() {
-                    match robust_move_file(&src_path, &dest_path) {
+                    // Checked while the file is still in temp, so a truncated download never reaches the target folder
+                    if general_config.verify_size && !state_audio_converted && !job_data.live_from_start {
+                        let expected: u64 = state_stream_sizes.values().sum();
+                        let actual = fs::metadata(&src_path).map(|m| m.len()).unwrap_or(0);
+                        if is_size_mismatch(expected, actual) {
+                            tracing::warn!("Size check failed for {}: {} bytes, expected about {}", job_id, actual, expected);
+                            let _ = tx_actor.send(JobMessage::JobError {
+                                id: job_id,
+                                error: format!("Downloaded file is {} but {} was expected", format_bytes(actual as f64), format_bytes(expected as f64)),
+                                code: Some(ErrorCode::SizeMismatch),
+                                hint: Some("The download looks truncated, often after a dropped connection. Retry it; the truncated file was not moved to the download folder.".to_string()),
+                            }).await;
+                            break;
+                        }
+                    }
+                    // Only a cross-volume copy reports; lossy sends are fine for progress
+                    let progress_tx = tx_actor.clone();
+                    let progress_title = state_clean_title.clone();
+                    let (move_src, move_dest) = (src_path.clone(), dest_path.clone());
+                    // A cross-volume copy of a large file takes minutes; keep it off the async workers
+                    let moved = tauri::async_runtime::spawn_blocking(move || {
+                        let mut last_percent = None;
+                        move_file_with_progress(&move_src, &move_dest, |done, total| {
+                            let percent = (done * 100).checked_div(total).unwrap_or(100);
+                            if last_percent == Some(percent) { return; }
+                            last_percent = Some(percent);
+                            let _ = progress_tx.try_send(JobMessage::UpdateProgress {
+                                id: job_id, percentage: percent as f32, speed: "N/A".to_string(), eta: "Done".to_string(),
+                                filename: progress_title.clone(), phase: format!("Moving to destination ({}%)", percent), speed_bps: None,
+                            });
+                        })
+                    })
+                    .await
+                    .unwrap_or_else(|e| Err(std::io::Error::other(e.to_string())));
+                    match moved {
                         Ok(_) => {
-                            let _ = tx_actor.send(JobMessage::JobCompleted { id: job_id, output_path: dest_path.to_string_lossy().to_string() }).await;
+                            let (chapter_temp, chapter_target, chapter_files) = (temp_dir.clone(), target_dir.clone(), state_chapter_files.clone());
+                            let chapters_moved = tauri::async_runtime::spawn_blocking(move || move_chapter_files(&chapter_temp, &chapter_target, &chapter_files))
+                                .await
+                                .unwrap_or_else(|e| Err(std::io::Error::other(e.to_string())));
+                            if let Err(e) = chapters_moved {
+                                let _ = tx_actor.send(JobMessage::JobError { id: job_id, error: format!("Moving chapter tracks failed: {}", e), code: None, hint: None }).await;
+                                break;
+                            }
+                            let info_src = info_json_path(&src_path);
+                            if info_src.exists() {
+                                if let Err(e) = robust_move_file(&info_src, &info_json_path(&dest_path)) {
+                                    tracing::warn!("Failed to move info JSON for {}: {}", job_id, e);
+                                }
+                            }
+                            if general_config.verify_output {
+                                let _ = tx_actor.send(JobMessage::UpdateProgress {
+                                    id: job_id, percentage: 100.0, speed: "N/A".to_string(), eta: "Done".to_string(),
+                                    filename: state_clean_title.clone(), phase: "Verifying Output".to_string(), speed_bps: None,
+                                }).await;
+                                if let Err(reason) = verify_media_file(&bin_dir, &dest_path).await {
+                                    tracing::warn!("Output verification failed for {}: {}", job_id, reason);
+                                    if !verify_retried {
+                                        // Drop the corrupt copy and download once more
+                                        verify_retried = true;
+                                        let _ = fs::remove_file(&dest_path);
+                                        continue;
+                                    }
+                                    let _ = tx_actor.send(JobMessage::JobError {
+                                        id: job_id,
+                                        error: format!("Output verification failed: {}", reason),
+                                        code: Some(ErrorCode::CorruptOutput),
+                                        hint: Some("The downloaded file appears truncated or corrupt. Retry the download; if it keeps failing, try a different format.".to_string()),
+                                    }).await;
+                                    break;
+                                }
+                            }
+                            if job_data.write_source_url {
+                                if let Err(e) = write_source_shortcut(&dest_path, &url) {
+                                    tracing::warn!("Failed to write source shortcut for {}: {}", job_id, e);
+                                }
+                            }
+                            let mut note = None;
+                            if job_data.download_storyboard {
+                                let _ = tx_actor.send(JobMessage::UpdateProgress {
+                                    id: job_id, percentage: 100.0, speed: "N/A".to_string(), eta: "Done".to_string(),
+                                    filename: state_clean_title.clone(), phase: "Downloading Storyboard".to_string(), speed_bps: None,
+                                }).await;
+                                let stem = dest_path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+                                match download_storyboard(&job_data, &general_config, &cookies, &bin_dir, &temp_dir, &target_dir, &stem).await {
+                                    Ok(Some(path)) => tracing::info!("Saved storyboard for {} to {}", job_id, path.display()),
+                                    Ok(None) => note = Some("No storyboard available for this video".to_string()),
+                                    Err(e) => {
+                                        tracing::warn!("Storyboard download failed for {}: {}", job_id, e);
+                                        note = Some("The storyboard could not be downloaded".to_string());
+                                    }
+                                }
+                            }
+                            let mut dest_path = dest_path;
+                            if let Some(profile) = job_data.transcode_profile.as_ref() {
+                                // Local work from here on; free the download slot
+                                release_network(&tx_actor, job_id, &mut network_released).await;
+                                let _ = tx_actor.send(JobMessage::UpdateProgress {
+                                    id: job_id, percentage: 0.0, speed: "N/A".to_string(), eta: "N/A".to_string(),
+                                    filename: state_clean_title.clone(), phase: "Transcoding".to_string(), speed_bps: None,
+                                }).await;
+                                let program = ffmpeg_program(&bin_dir, general_config.ffmpeg_path.as_deref());
+                                let duration = media_duration(&bin_dir, &dest_path).await;
+                                let start_tx = tx_actor.clone();
+                                let progress_tx = tx_actor.clone();
+                                let progress_title = state_clean_title.clone();
+                                let mut last_percent = None;
+                                let transcoded = transcode_file(&program, &dest_path, duration, profile,
+                                    // ffmpeg becomes the job's process, so cancelling stops the transcode
+                                    |pid| { let _ = start_tx.try_send(JobMessage::ProcessStarted { id: job_id, pid }); },
+                                    |percent| {
+                                        let whole = percent as u32;
+                                        if last_percent == Some(whole) { return; }
+                                        last_percent = Some(whole);
+                                        let _ = progress_tx.try_send(JobMessage::UpdateProgress {
+                                            id: job_id, percentage: percent, speed: "N/A".to_string(), eta: "N/A".to_string(),
+                                            filename: progress_title.clone(), phase: format!("Transcoding ({}%)", whole), speed_bps: None,
+                                        });
+                                    },
+                                ).await;
+                                match transcoded {
+                                    Ok(path) => dest_path = path,
+                                    Err(e) => {
+                                        tracing::warn!("Transcoding failed for {}: {}", job_id, e);
+                                        let _ = tx_actor.send(JobMessage::JobError {
+                                            id: job_id,
+                                            error: format!("Transcoding failed: {}", e),
+                                            code: None,
+                                            hint: Some(format!("The download itself finished and was kept at {}.", dest_path.display())),
+                                        }).await;
+                                        break;
+                                    }
+                                }
+                            }
+                            if let Some(subtitle) = job_data.external_subtitle.as_deref() {
+                                release_network(&tx_actor, job_id, &mut network_released).await;
+                                let _ = tx_actor.send(JobMessage::UpdateProgress {
+                                    id: job_id, percentage: 100.0, speed: "N/A".to_string(), eta: "Done".to_string(),
+                                    filename: state_clean_title.clone(), phase: "Embedding External Subtitles".to_string(), speed_bps: None,
+                                }).await;
+                                let program = ffmpeg_program(&bin_dir, general_config.ffmpeg_path.as_deref());
+                                let start_tx = tx_actor.clone();
+                                let embedded = embed_subtitle_file(&program, &dest_path, Path::new(subtitle),
+                                    |pid| { let _ = start_tx.try_send(JobMessage::ProcessStarted { id: job_id, pid }); },
+                                ).await;
+                                if let Err(e) = embedded {
+                                    tracing::warn!("Embedding external subtitles failed for {}: {}", job_id, e);
+                                    let _ = tx_actor.send(JobMessage::JobError {
+                                        id: job_id,
+                                        error: format!("Embedding external subtitles failed: {}", e),
+                                        code: None,
+                                        hint: Some(format!("The download itself finished and was kept at {}.", dest_path.display())),
+                                    }).await;
+                                    break;
+                                }
+                            }
+                            let output_path = dest_path.to_string_lossy().to_string();
+                            let mut completed = completed_message(job_id, &dest_path, state_format_id.clone());
+                            if let JobMessage::JobCompleted { note: completed_note, .. } = &mut completed {
+                                *completed_note = note;
+                            }
+                            let _ = tx_actor.send(completed).await;
+
+                            if let Some(hook) = general_config.post_hook_command.clone().filter(|h| !h.trim().is_empty()) {
+                                let vars = HookVariables {
+                                    path: output_path.clone(),
+                                    title: state_clean_title.clone().unwrap_or_default(),
+                                    url: url.clone(),
+                                };
+                                let delete_after = general_config.delete_after_hook;
+                                let hook_app = app_handle.clone();
+                                // Detached: the hook must not hold the download slot
+                                tauri::async_runtime::spawn(async move {
+                                    let result = run_post_hook(hook_app.clone(), job_id, hook, vars).await;
+                                    if delete_after && result.success {
+                                        delete_after_hook(&hook_app, job_id, Path::new(&output_path));
+                                    }
+                                });
+                            }
                             break;
                         },
                         Err(e) => {
-                            let _ = tx_actor.send(JobMessage::JobError { id: job_id, error: format!("Move failed: {}", e) }).await;
+                            let (error, hint) = describe_move_error(&e, &dest_path);
+                            let _ = tx_actor.send(JobMessage::JobError { id: job_id, error, code: None, hint }).await;
                             break;
                         }
                     }
                 } else {
-                     let _ = tx_actor.send(JobMessage::JobError { id: job_id, error: "Output missing in temp dir".into() }).await;
+                     let _ = tx_actor.send(JobMessage::JobError { id: job_id, error: "Output missing in temp dir".into(), code: None, hint: None }).await;
                      break;
                 }
             } else {
-                let _ = tx_actor.send(JobMessage::JobError { id: job_id, error: "Filename undetermined".into() }).await;
+                let _ = tx_actor.send(JobMessage::JobError { id: job_id, error: "Filename undetermined".into(), code: None, hint: None }).await;
                 break;
             }
         } else {
             let log_blob = captured_logs.join("\n");
+            if rate_limit_retries < RATE_LIMIT_MAX_RETRIES {
+                if let Some(wait) = rate_limit_backoff(&log_blob, rate_limit_retries) {
+                    rate_limit_retries += 1;
+                    tracing::warn!("Job {} rate limited; retry {}/{} in {}s", job_id, rate_limit_retries, RATE_LIMIT_MAX_RETRIES, wait);
+                    // Waiting isn't downloading: let another job have the slot until the retry takes it back
+                    release_network(&tx_actor, job_id, &mut network_released).await;
+                    let mut remaining = wait;
+                    while remaining > 0 {
+                        let _ = tx_actor.send(JobMessage::UpdateProgress {
+                            id: job_id, percentage: state_percentage, speed: "N/A".to_string(), eta: format_eta(remaining),
+                            filename: state_clean_title.clone(), phase: format!("Rate limited, waiting {}s", remaining), speed_bps: None,
+                        }).await;
+                        let step = remaining.min(RATE_LIMIT_PROGRESS_INTERVAL);
+                        tokio::time::sleep(std::time::Duration::from_secs(step)).await;
+                        remaining -= step;
+                    }
+                    continue;
+                }
+            }
+            // With browser cookies (re-read on each attempt) a bot check often clears; retry once
+            if !bot_check_retried && browser_cookie_source(&general_config).is_some() && BOT_CHECK_REGEX.is_match(&log_blob) {
+                bot_check_retried = true;
+                tracing::warn!("Job {} hit YouTube's bot check; retrying once in {}s", job_id, BOT_CHECK_RETRY_DELAY);
+                for remaining in (1..=BOT_CHECK_RETRY_DELAY).rev() {
+                    let _ = tx_actor.send(JobMessage::UpdateProgress {
+                        id: job_id, percentage: state_percentage, speed: "N/A".to_string(), eta: format_eta(remaining),
+                        filename: state_clean_title.clone(), phase: format!("Bot check, retrying in {}s", remaining), speed_bps: None,
+                    }).await;
+                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                }
+                continue;
+            }
+            // A thumbnail that ffmpeg couldn't embed gets one more try as jpg
+            if job_data.embed_thumbnail && !force_jpg_thumbnail && THUMBNAIL_EMBED_FAILED_REGEX.is_match(&log_blob) {
+                tracing::info!("Thumbnail embedding failed for {}; retrying with jpg conversion", job_id);
+                force_jpg_thumbnail = true;
+                continue;
+            }
             let is_filesystem_error = FILESYSTEM_ERROR_REGEX.is_match(&log_blob);
             
-            if !job_data.restrict_filenames && is_filesystem_error {
+            if !job_data.restrict_filenames && is_filesystem_error && general_config.auto_sanitize_retry {
+                tracing::info!("Filesystem error for {}; retrying with restricted filenames", job_id);
+                let _ = app_handle.emit_all("filenames-sanitized", FilenamesSanitizedPayload {
+                    job_id,
+                    message: "The filename couldn't be written, so it is being retried with special characters removed.".to_string(),
+                });
                 job_data.restrict_filenames = true;
                 continue; // Retry Loop
             }
 
+            let (code, hint) = classify_error(&log_blob, &general_config);
             let _ = tx_actor.send(JobMessage::JobError { 
                 id: job_id, 
-                error: format!("Exit Code {}. Logs: {}", status.code().unwrap_or(-1), log_blob) 
+                error: format!("Exit Code {}. Logs: {}", status.code().unwrap_or(-1), log_blob),
+                code,
+                hint,
             }).await;
             break;
         }
     }
     
-    let _ = tx_actor.send(JobMessage::WorkerFinished).await;
-}
\ No newline at end of file
+    let _ = tx_actor.send(JobMessage::WorkerFinished { id: job_id }).await;
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format_args(preset: DownloadFormatPreset, resolution: &str, config: &GeneralConfig) -> Vec<String> {
+        build_format_args(&FormatOptions::new(preset, resolution.to_string()), config)
+    }
+
+    fn arg_after<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+        args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).map(String::as_str)
+    }
+
+    #[test]
+    fn global_max_height_wins_over_higher_job_resolution() {
+        let config = GeneralConfig { global_max_height: Some(720), ..Default::default() };
+        let args = format_args(DownloadFormatPreset::BestMp4, "1080p", &config);
+        assert_eq!(arg_after(&args, "-f"), Some("bestvideo[height<=720]+bestaudio"));
+    }
+
+    #[test]
+    fn global_max_height_keeps_lower_job_resolution() {
+        let config = GeneralConfig { global_max_height: Some(1080), ..Default::default() };
+        let args = format_args(DownloadFormatPreset::BestMp4, "480p", &config);
+        assert_eq!(arg_after(&args, "-f"), Some("bestvideo[height<=480]+bestaudio"));
+    }
+
+    #[test]
+    fn global_max_height_clamps_best() {
+        let config = GeneralConfig { global_max_height: Some(720), ..Default::default() };
+        let args = format_args(DownloadFormatPreset::Best, "best", &config);
+        assert_eq!(arg_after(&args, "-f"), Some("bestvideo[height<=720]+bestaudio/best[height<=720]"));
+    }
+
+    #[test]
+    fn best_without_cap_leaves_selection_to_yt_dlp() {
+        let args = format_args(DownloadFormatPreset::Best, "best", &GeneralConfig::default());
+        assert!(arg_after(&args, "-f").is_none());
+    }
+
+    fn audio_args(preset: DownloadFormatPreset, bitrate: Option<u32>) -> Vec<String> {
+        let mut opts = FormatOptions::new(preset, "best".to_string());
+        opts.audio_bitrate = bitrate;
+        build_format_args(&opts, &GeneralConfig::default())
+    }
+
+    #[test]
+    fn lossy_audio_uses_requested_bitrate() {
+        for preset in [DownloadFormatPreset::AudioMp3, DownloadFormatPreset::AudioM4a] {
+            assert_eq!(arg_after(&audio_args(preset, Some(128)), "--audio-quality"), Some("128K"));
+        }
+    }
+
+    #[test]
+    fn lossy_audio_defaults_to_best_vbr() {
+        assert_eq!(arg_after(&audio_args(DownloadFormatPreset::AudioMp3, None), "--audio-quality"), Some("0"));
+    }
+
+    #[test]
+    fn flac_ignores_bitrate() {
+        let args = audio_args(DownloadFormatPreset::AudioFlac, Some(128));
+        assert_eq!(arg_after(&args, "--audio-format"), Some("flac"));
+        assert_eq!(arg_after(&args, "--audio-quality"), Some("0"));
+    }
+
+    fn hdr_sort(preference: HdrPreference) -> Option<String> {
+        let config = GeneralConfig { hdr_preference: preference, ..Default::default() };
+        arg_after(&format_args(DownloadFormatPreset::BestMkv, "best", &config), "-S").map(str::to_string)
+    }
+
+    #[test]
+    fn hdr_keep_adds_no_sort() {
+        assert_eq!(hdr_sort(HdrPreference::Keep), None);
+    }
+
+    #[test]
+    fn hdr_prefer_sdr_and_prefer_hdr_sort() {
+        assert_eq!(hdr_sort(HdrPreference::PreferSdr).as_deref(), Some("hdr:SDR"));
+        assert_eq!(hdr_sort(HdrPreference::PreferHdr).as_deref(), Some("hdr"));
+    }
+
+    #[test]
+    fn hdr_sort_composes_with_size_target_and_format_sort() {
+        let config = GeneralConfig {
+            hdr_preference: HdrPreference::PreferSdr,
+            format_sort: Some("vcodec:h264".into()),
+            ..Default::default()
+        };
+        let mut opts = FormatOptions::new(DownloadFormatPreset::BestMp4, "1080p".to_string());
+        opts.target_filesize = Some("500M".into());
+        let args = build_format_args(&opts, &config);
+        assert_eq!(arg_after(&args, "-f"), Some("bestvideo[height<=1080]+bestaudio"));
+        assert_eq!(arg_after(&args, "-S"), Some("filesize:500M,hdr:SDR,vcodec:h264"));
+    }
+
+    #[test]
+    fn hdr_preference_is_ignored_for_audio() {
+        let config = GeneralConfig { hdr_preference: HdrPreference::PreferHdr, ..Default::default() };
+        assert!(arg_after(&format_args(DownloadFormatPreset::AudioMp3, "best", &config), "-S").is_none());
+    }
+
+    fn fixed_now() -> chrono::DateTime<chrono::Local> {
+        use chrono::TimeZone;
+        chrono::Local.with_ymd_and_hms(2024, 3, 7, 15, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn date_subfolder_appends_formatted_date() {
+        let target = PathBuf::from("downloads");
+        assert_eq!(date_subfolder(target.clone(), Some("%Y/%m"), fixed_now()), target.join("2024/03"));
+        assert_eq!(date_subfolder(target.clone(), Some(" %Y-%m-%d "), fixed_now()), target.join("2024-03-07"));
+    }
+
+    #[test]
+    fn date_subfolder_ignores_missing_or_invalid_format() {
+        let target = PathBuf::from("downloads");
+        assert_eq!(date_subfolder(target.clone(), None, fixed_now()), target);
+        assert_eq!(date_subfolder(target.clone(), Some("  "), fixed_now()), target);
+        assert_eq!(date_subfolder(target.clone(), Some("../%Y"), fixed_now()), target);
+    }
+
+    #[test]
+    fn date_subfolder_format_validation() {
+        assert!(validate_date_subfolder_format("%Y/%m").is_ok());
+        assert!(validate_date_subfolder_format("archive").is_err());
+        assert!(validate_date_subfolder_format("%Q").is_err());
+        assert!(validate_date_subfolder_format("/%Y").is_err());
+        assert!(validate_date_subfolder_format("%Y/../%m").is_err());
+        assert!(validate_date_subfolder_format("%H:%M").is_err());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn detects_unc_paths() {
+        assert!(is_unc_path(r"\\server\share"));
+        assert!(is_unc_path(r"\\server\share\Videos"));
+        assert!(is_unc_path("//server/share"));
+        assert!(is_unc_path(r"\\?\UNC\server\share\Videos"));
+        assert!(is_unc_path(r"\\?\unc\server\share"));
+        assert!(!is_unc_path(r"C:\Users\me\Videos"));
+        assert!(!is_unc_path(r"\\?\C:\Users\me\Videos"));
+        assert!(!is_unc_path("D:/Downloads"));
+    }
+
+
+    #[test]
+    fn resume_args_follow_redownload_setting() {
+        assert_eq!(resume_args(false), ["--continue"]);
+        assert_eq!(resume_args(true), ["--no-continue", "--force-overwrites"]);
+    }
+
+
+    fn queued_job(url: &str) -> QueuedJob {
+        serde_json::from_value(serde_json::json!({
+            "id": uuid::Uuid::new_v4(),
+            "url": url,
+            "download_path": null,
+            "format_preset": "best",
+            "video_resolution": "best",
+            "embed_metadata": false,
+            "embed_thumbnail": false,
+            "filename_template": "%(title)s.%(ext)s",
+            "restrict_filenames": false,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn retry_resumes_from_the_same_temp_dir() {
+        let url = "https://www.youtube.com/watch?v=dQw4w9WgXcQ";
+        let root = Path::new("/tmp/temp_downloads");
+        // A manual retry queues a new job for the same URL; it must land on the first attempt's partials
+        let (first, retry) = (queued_job(url), queued_job(url));
+        assert_ne!(first.id, retry.id);
+        assert_eq!(job_temp_dir(root, &first.url), job_temp_dir(root, &retry.url));
+        assert_eq!(job_temp_dir(root, url), job_temp_dir(root, &format!(" {} ", url)));
+        assert_ne!(job_temp_dir(root, url), job_temp_dir(root, "https://www.youtube.com/watch?v=other"));
+
+        let mut config = GeneralConfig::default();
+        let args = download_args(&retry, &config, 12, false);
+        assert!(args.iter().any(|a| a == "--continue"));
+        assert!(!args.iter().any(|a| a == "--no-continue"));
+
+        config.redownload_existing = true;
+        let args = download_args(&retry, &config, 12, false);
+        assert!(args.iter().any(|a| a == "--no-continue"));
+        assert!(!args.iter().any(|a| a == "--continue"));
+    }
+}
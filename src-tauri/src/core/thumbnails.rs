@@ -0,0 +1,92 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+use base64::Engine;
+
+use crate::core::deps::get_http_client;
+
+/// Thumbnails kept on disk; the least recently written are evicted beyond this.
+const MAX_CACHED_THUMBNAILS: usize = 300;
+
+/// Thumbnails larger than this are not inlined (the UI can still use the remote URL).
+const MAX_THUMBNAIL_BYTES: usize = 2 * 1024 * 1024;
+
+fn cache_dir() -> PathBuf {
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    home.join(".multiyt-dlp").join("thumbnails")
+}
+
+/// Cache file name for a video id; ids are host-provided, so anything unusual is dropped.
+fn cache_path(video_id: &str) -> Option<PathBuf> {
+    let safe = !video_id.is_empty()
+        && video_id.len() <= 128
+        && video_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    safe.then(|| cache_dir().join(format!("{}.img", video_id)))
+}
+
+/// Sniffs the image type from its header; hosts mostly serve JPEG or WebP.
+fn mime_type(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(&[0x89, b'P', b'N', b'G']) {
+        "image/png"
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        "image/webp"
+    } else {
+        "image/jpeg"
+    }
+}
+
+fn to_data_uri(bytes: &[u8]) -> String {
+    format!("data:{};base64,{}", mime_type(bytes), base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+/// Removes the oldest cache entries until at most `MAX_CACHED_THUMBNAILS` remain.
+fn prune_cache() {
+    let Ok(read_dir) = std::fs::read_dir(cache_dir()) else { return };
+    let mut files: Vec<(SystemTime, PathBuf)> = read_dir
+        .flatten()
+        .filter_map(|e| {
+            let modified = e.metadata().ok()?.modified().ok()?;
+            Some((modified, e.path()))
+        })
+        .collect();
+    if files.len() <= MAX_CACHED_THUMBNAILS { return; }
+
+    files.sort_by_key(|(modified, _)| *modified);
+    let excess = files.len() - MAX_CACHED_THUMBNAILS;
+    for (_, path) in files.into_iter().take(excess) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Returns the thumbnail as a data URI, from the on-disk cache when `video_id` was fetched before.
+pub async fn fetch_inline(thumbnail_url: &str, video_id: Option<&str>) -> Result<String, String> {
+    let cached = video_id.and_then(cache_path);
+    if let Some(path) = &cached {
+        if let Ok(bytes) = tokio::fs::read(path).await {
+            return Ok(to_data_uri(&bytes));
+        }
+    }
+
+    let resp = get_http_client()?
+        .get(thumbnail_url)
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("Thumbnail request failed: {}", resp.status()));
+    }
+    let bytes = resp.bytes().await.map_err(|e| e.to_string())?;
+    if bytes.len() > MAX_THUMBNAIL_BYTES {
+        return Err("Thumbnail is too large to inline.".to_string());
+    }
+
+    if let Some(path) = cached {
+        let _ = tokio::fs::create_dir_all(cache_dir()).await;
+        if let Err(e) = tokio::fs::write(&path, &bytes).await {
+            tracing::warn!("Failed to cache thumbnail {}: {}", path.display(), e);
+        } else {
+            let _ = tauri::async_runtime::spawn_blocking(prune_cache).await;
+        }
+    }
+
+    Ok(to_data_uri(&bytes))
+}
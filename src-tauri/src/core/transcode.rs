@@ -0,0 +1,199 @@
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+
+use crate::models::{TranscodeCodec, TranscodeProfile};
+
+const FFMPEG_EXE: &str = if cfg!(windows) { "ffmpeg.exe" } else { "ffmpeg" };
+
+/// The binary a configured `ffmpeg_path` names: the file itself, or `ffmpeg[.exe]` inside it
+/// when it is a folder (yt-dlp's `--ffmpeg-location` takes either).
+pub fn configured_ffmpeg_binary(path: &Path) -> PathBuf {
+    if path.is_dir() { path.join(FFMPEG_EXE) } else { path.to_path_buf() }
+}
+
+/// Resolves ffmpeg the way downloads do: the configured `ffmpeg_path`, then the managed
+/// binary in `bin_dir`, then whatever is on PATH.
+pub fn ffmpeg_program(bin_dir: &Path, configured: Option<&str>) -> PathBuf {
+    if let Some(path) = configured.map(str::trim).filter(|p| !p.is_empty()) {
+        return configured_ffmpeg_binary(Path::new(path));
+    }
+    let local = bin_dir.join(FFMPEG_EXE);
+    if local.exists() { local } else { PathBuf::from("ffmpeg") }
+}
+
+/// Where the transcode of `source` ends up: `<stem>.<codec>.<ext>` beside the original when it
+/// is kept, else `<stem>.<ext>` (replacing it if the container is unchanged).
+pub fn transcode_output_path(source: &Path, profile: &TranscodeProfile) -> PathBuf {
+    let stem = source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let name = if profile.keep_original {
+        format!("{}.{}.{}", stem, profile.codec.label(), profile.codec.container())
+    } else {
+        format!("{}.{}", stem, profile.codec.container())
+    };
+    source.with_file_name(name)
+}
+
+fn build_transcode_args(source: &Path, dest: &Path, profile: &TranscodeProfile) -> Vec<String> {
+    let mut args: Vec<String> = ["-hide_banner", "-nostdin", "-y", "-i"].iter().map(|s| s.to_string()).collect();
+    args.push(source.to_string_lossy().to_string());
+    if let Some(height) = profile.max_height {
+        // Never upscale; -2 keeps the width even as most encoders require
+        args.push("-vf".into());
+        args.push(format!("scale=-2:'min({},ih)'", height));
+    }
+    let (video_encoder, audio_encoder) = match profile.codec {
+        TranscodeCodec::H264 => ("libx264", "aac"),
+        TranscodeCodec::H265 => ("libx265", "aac"),
+        TranscodeCodec::Vp9 => ("libvpx-vp9", "libopus"),
+    };
+    args.extend(["-c:v".into(), video_encoder.into(), "-crf".into(), profile.crf.unwrap_or(profile.codec.default_crf()).to_string()]);
+    if profile.codec == TranscodeCodec::Vp9 {
+        // Constant-quality mode for libvpx needs an explicit zero bitrate
+        args.extend(["-b:v".into(), "0".into()]);
+    } else {
+        args.extend(["-preset".into(), "medium".into()]);
+    }
+    if profile.codec == TranscodeCodec::H265 {
+        // Lets Apple players recognise HEVC in MP4
+        args.extend(["-tag:v".into(), "hvc1".into()]);
+    }
+    args.extend(["-c:a".into(), audio_encoder.into(), "-b:a".into(), format!("{}k", profile.audio_bitrate.unwrap_or(160))]);
+    if profile.codec.container() == "mp4" {
+        args.extend(["-movflags".into(), "+faststart".into()]);
+    }
+    args.extend(["-progress".into(), "pipe:1".into(), "-nostats".into()]);
+    args.push(dest.to_string_lossy().to_string());
+    args
+}
+
+/// Transcodes `source` per `profile` and returns the resulting file. `on_start` gets ffmpeg's
+/// pid (so the job can be cancelled) and `on_progress` a 0-100 percentage when the duration is known.
+/// The output is written to a temporary name first, so a failed run never clobbers the original.
+pub async fn transcode_file(
+    program: &Path,
+    source: &Path,
+    duration_secs: Option<f64>,
+    profile: &TranscodeProfile,
+    mut on_start: impl FnMut(u32),
+    mut on_progress: impl FnMut(f32),
+) -> Result<PathBuf, String> {
+    let dest = transcode_output_path(source, profile);
+    let partial = dest.with_file_name(format!(
+        "{}.transcoding.{}",
+        dest.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+        profile.codec.container(),
+    ));
+
+    let mut cmd = Command::new(program);
+    cmd.args(build_transcode_args(source, &partial, profile))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    #[cfg(target_os = "windows")]
+    { cmd.creation_flags(0x08000000); }
+
+    let mut child = cmd.spawn().map_err(|e| format!("Could not start ffmpeg: {}", e))?;
+    if let Some(pid) = child.id() {
+        on_start(pid);
+    }
+
+    let stdout = child.stdout.take().expect("Failed to capture stdout");
+    let stderr = child.stderr.take().expect("Failed to capture stderr");
+    // ffmpeg is chatty on stderr; keep just the tail for the error message
+    let stderr_task = tokio::spawn(async move {
+        let mut lines = BufReader::new(stderr).lines();
+        let mut tail: Vec<String> = Vec::new();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if tail.len() == 20 { tail.remove(0); }
+            tail.push(line);
+        }
+        tail.join("\n")
+    });
+
+    let mut lines = BufReader::new(stdout).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        // `out_time_us` (and the misnamed `out_time_ms`) are both microseconds
+        let micros = line.strip_prefix("out_time_us=").or_else(|| line.strip_prefix("out_time_ms="));
+        if let (Some(micros), Some(total)) = (micros.and_then(|m| m.trim().parse::<f64>().ok()), duration_secs) {
+            if total > 0.0 {
+                on_progress(((micros / 1_000_000.0) / total * 100.0).clamp(0.0, 100.0) as f32);
+            }
+        }
+    }
+
+    let status = child.wait().await.map_err(|e| e.to_string())?;
+    let stderr_tail = stderr_task.await.unwrap_or_default();
+    if !status.success() {
+        let _ = std::fs::remove_file(&partial);
+        return Err(format!("ffmpeg exited with {}: {}", status.code().unwrap_or(-1), stderr_tail.trim()));
+    }
+
+    if !profile.keep_original {
+        std::fs::remove_file(source).map_err(|e| format!("Could not remove the original: {}", e))?;
+    }
+    std::fs::rename(&partial, &dest).map_err(|e| format!("Could not rename the transcoded file: {}", e))?;
+    Ok(dest)
+}
+
+/// Subtitle files accepted for `QueuedJob::external_subtitle`.
+pub const EXTERNAL_SUBTITLE_FORMATS: &[&str] = &["srt", "ass", "vtt"];
+
+/// Subtitle codec `container` can hold: MP4 only takes mov_text and WebM only WebVTT,
+/// while Matroska keeps the file's own format. `None` for containers without subtitle tracks.
+fn subtitle_codec_for(container: &str) -> Option<&'static str> {
+    match container {
+        "mp4" | "m4v" | "mov" => Some("mov_text"),
+        "webm" => Some("webvtt"),
+        "mkv" => Some("copy"),
+        _ => None,
+    }
+}
+
+/// Muxes `subtitle` into `video` as an extra track, keeping every existing stream as is.
+/// Like `transcode_file`, the result goes to a temporary name and only replaces `video` on success.
+pub async fn embed_subtitle_file(
+    program: &Path,
+    video: &Path,
+    subtitle: &Path,
+    mut on_start: impl FnMut(u32),
+) -> Result<(), String> {
+    let container = video.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let codec = subtitle_codec_for(&container)
+        .ok_or_else(|| format!("{} files can't hold subtitle tracks", container.to_uppercase()))?;
+    let partial = video.with_file_name(format!(
+        "{}.subbing.{}",
+        video.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+        container,
+    ));
+
+    let mut cmd = Command::new(program);
+    cmd.args(["-hide_banner", "-nostdin", "-y", "-i"]).arg(video)
+        .arg("-i").arg(subtitle)
+        .args(["-map", "0", "-map", "1:0", "-c", "copy", "-c:s", codec])
+        .arg(&partial)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    #[cfg(target_os = "windows")]
+    { cmd.creation_flags(0x08000000); }
+
+    let child = cmd.spawn().map_err(|e| format!("Could not start ffmpeg: {}", e))?;
+    if let Some(pid) = child.id() {
+        on_start(pid);
+    }
+    let output = child.wait_with_output().await.map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&partial);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let tail: Vec<&str> = stderr.lines().rev().take(20).collect();
+        return Err(format!(
+            "ffmpeg exited with {}: {}",
+            output.status.code().unwrap_or(-1),
+            tail.into_iter().rev().collect::<Vec<_>>().join("\n").trim(),
+        ));
+    }
+
+    std::fs::rename(&partial, video).map_err(|e| format!("Could not replace the file: {}", e))
+}
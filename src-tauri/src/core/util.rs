@@ -0,0 +1,124 @@
+//! Human-readable size/speed/time formatting (and size parsing) shared across modules.
+
+use crate::config::SpeedUnits;
+
+/// Formats a byte count with binary units ("1.50 MiB"); NaN/infinite become "N/A".
+pub fn format_bytes(bytes: f64) -> String {
+    format_bytes_in(bytes, SpeedUnits::Binary)
+}
+
+/// `format_bytes` in the given units: base-1024 "KiB/MiB/GiB" or base-1000 "KB/MB/GB".
+pub fn format_bytes_in(bytes: f64, units: SpeedUnits) -> String {
+    if bytes.is_nan() || bytes.is_infinite() { return "N/A".to_string(); }
+    let (base, suffixes) = match units {
+        SpeedUnits::Binary => (1024.0, ["KiB", "MiB", "GiB"]),
+        SpeedUnits::Decimal => (1000.0, ["KB", "MB", "GB"]),
+    };
+    let kilo: f64 = base;
+    let mega = kilo * base;
+    let giga = mega * base;
+    if bytes >= giga { format!("{:.2} {}", bytes / giga, suffixes[2]) }
+    else if bytes >= mega { format!("{:.2} {}", bytes / mega, suffixes[1]) }
+    else if bytes >= kilo { format!("{:.2} {}", bytes / kilo, suffixes[0]) }
+    else { format!("{:.0} B", bytes) }
+}
+
+/// Parses yt-dlp style sizes ("500M", "1.5G", "800K", "1024") into bytes.
+pub fn parse_byte_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let split = value.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(value.len());
+    let (number, suffix) = value.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier: f64 = match suffix.trim().to_ascii_uppercase().trim_end_matches(['B', 'I']) {
+        "" => 1.0,
+        "K" => 1024.0,
+        "M" => 1024.0 * 1024.0,
+        "G" => 1024.0 * 1024.0 * 1024.0,
+        "T" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    if number <= 0.0 { return None; }
+    Some((number * multiplier) as u64)
+}
+
+pub fn format_speed(bytes_per_sec: f64, units: SpeedUnits) -> String {
+    if bytes_per_sec.is_nan() || bytes_per_sec.is_infinite() { return "N/A".to_string(); }
+    format!("{}/s", format_bytes_in(bytes_per_sec, units))
+}
+
+/// "MM:SS", or "HH:MM:SS" once the ETA reaches an hour.
+pub fn format_eta(seconds: u64) -> String {
+    let h = seconds / 3600;
+    let m = (seconds % 3600) / 60;
+    let s = seconds % 60;
+    if h > 0 { format!("{:02}:{:02}:{:02}", h, m, s) }
+    else { format!("{:02}:{:02}", m, s) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speed_is_na_for_nan_and_infinite() {
+        assert_eq!(format_speed(f64::NAN, SpeedUnits::Binary), "N/A");
+        assert_eq!(format_speed(f64::INFINITY, SpeedUnits::Binary), "N/A");
+        assert_eq!(format_speed(f64::NEG_INFINITY, SpeedUnits::Decimal), "N/A");
+    }
+
+    #[test]
+    fn zero_bytes() {
+        assert_eq!(format_bytes(0.0), "0 B");
+        assert_eq!(format_speed(0.0, SpeedUnits::Binary), "0 B/s");
+    }
+
+    #[test]
+    fn binary_unit_boundaries() {
+        assert_eq!(format_bytes(1023.0), "1023 B");
+        assert_eq!(format_bytes(1024.0), "1.00 KiB");
+        assert_eq!(format_bytes(1024.0 * 1024.0 - 1.0), "1024.00 KiB");
+        assert_eq!(format_bytes(1024.0 * 1024.0), "1.00 MiB");
+        assert_eq!(format_bytes(1024.0 * 1024.0 * 1024.0), "1.00 GiB");
+        assert_eq!(format_speed(1.5 * 1024.0 * 1024.0, SpeedUnits::Binary), "1.50 MiB/s");
+    }
+
+    #[test]
+    fn eta_minutes_and_hours() {
+        assert_eq!(format_eta(0), "00:00");
+        assert_eq!(format_eta(59), "00:59");
+        assert_eq!(format_eta(3599), "59:59");
+        assert_eq!(format_eta(3600), "01:00:00");
+        assert_eq!(format_eta(2 * 3600 + 5 * 60 + 7), "02:05:07");
+        assert_eq!(format_eta(100 * 3600), "100:00:00");
+    }
+
+    #[test]
+    fn binary_and_decimal_kilo_boundaries() {
+        assert_eq!(format_bytes_in(999.0, SpeedUnits::Binary), "999 B");
+        assert_eq!(format_bytes_in(999.0, SpeedUnits::Decimal), "999 B");
+        assert_eq!(format_bytes_in(1000.0, SpeedUnits::Binary), "1000 B");
+        assert_eq!(format_bytes_in(1000.0, SpeedUnits::Decimal), "1.00 KB");
+        assert_eq!(format_bytes_in(1023.0, SpeedUnits::Binary), "1023 B");
+        assert_eq!(format_bytes_in(1023.0, SpeedUnits::Decimal), "1.02 KB");
+        assert_eq!(format_bytes_in(1024.0, SpeedUnits::Binary), "1.00 KiB");
+        assert_eq!(format_bytes_in(1024.0, SpeedUnits::Decimal), "1.02 KB");
+    }
+
+    #[test]
+    fn binary_and_decimal_mega_and_giga_boundaries() {
+        assert_eq!(format_bytes_in(999_999.0, SpeedUnits::Decimal), "1000.00 KB");
+        assert_eq!(format_bytes_in(1_000_000.0, SpeedUnits::Decimal), "1.00 MB");
+        assert_eq!(format_bytes_in(1_000_000.0, SpeedUnits::Binary), "976.56 KiB");
+        assert_eq!(format_bytes_in(1_048_576.0, SpeedUnits::Binary), "1.00 MiB");
+        assert_eq!(format_bytes_in(1_048_576.0, SpeedUnits::Decimal), "1.05 MB");
+        assert_eq!(format_bytes_in(1_000_000_000.0, SpeedUnits::Decimal), "1.00 GB");
+        assert_eq!(format_bytes_in(1_000_000_000.0, SpeedUnits::Binary), "953.67 MiB");
+        assert_eq!(format_bytes_in(1_073_741_824.0, SpeedUnits::Binary), "1.00 GiB");
+    }
+
+    #[test]
+    fn speed_uses_configured_units() {
+        assert_eq!(format_speed(2_000_000.0, SpeedUnits::Decimal), "2.00 MB/s");
+        assert_eq!(format_speed(2_097_152.0, SpeedUnits::Binary), "2.00 MiB/s");
+    }
+}
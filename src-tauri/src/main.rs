@@ -2,6 +2,7 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use std::sync::Arc;
+use notify::{RecursiveMode, Watcher};
 use tauri::{Manager, WindowEvent};
 use tokio::sync::mpsc;
 use std::time::Duration;
@@ -48,17 +49,22 @@ fn main() {
     let config_manager = Arc::new(ConfigManager::new());
     let initial_config = config_manager.get_config();
     let log_manager = LogManager::init(&initial_config.general.log_level);
+    core::crash::set_reports_enabled(initial_config.general.write_crash_reports);
+    core::crash::install_panic_hook();
 
     // Persistence config auto-save channel
     let config_manager_setup = config_manager.clone();
     let config_manager_event = config_manager.clone();
     let config_manager_saver = config_manager.clone();
+    let config_manager_watcher = config_manager.clone();
     let (tx_save, mut rx_save) = mpsc::unbounded_channel::<()>();
 
     tauri::Builder::default()
         .manage(config_manager)
         .manage(log_manager)
         .setup(move |app| {
+            core::crash::attach_app_handle(app.handle());
+
             // Initialize the Actor Handle here
             let job_manager_handle = JobManagerHandle::new(app.handle());
             app.manage(job_manager_handle);
@@ -77,10 +83,67 @@ fn main() {
             
             tracing::info!("Application startup complete. Window initialized.");
 
+            if config.general.enable_http_api {
+                let token = config.general.http_api_token.clone().filter(|t| !t.trim().is_empty());
+                match (config.general.http_api_bind.trim().parse(), token) {
+                    (Ok(bind), Some(token)) => {
+                        tauri::async_runtime::spawn(core::http_api::serve(app.handle(), bind, token));
+                    }
+                    _ => tracing::error!("HTTP API enabled but its address or token is invalid; not starting it"),
+                }
+            }
+
+            if config.general.auto_resume_on_startup {
+                let handle = app.handle();
+                tauri::async_runtime::spawn(async move {
+                    commands::system::DEPENDENCIES_READY.notified().await;
+                    let resumed = handle.state::<JobManagerHandle>().auto_resume_pending().await;
+                    if !resumed.is_empty() {
+                        tracing::info!("Auto-resumed {} pending job(s)", resumed.len());
+                        let _ = handle.emit_all("jobs-resumed", resumed);
+                    }
+                });
+            }
+
+            // Hand edits to config.json (e.g. via open_config_file) are applied live. The folder is
+            // watched rather than the file, since editors often save by replacing it; if no watcher
+            // can be set up, the file is polled instead.
+            let watch_handle = app.handle();
+            tauri::async_runtime::spawn(async move {
+                let (tx_fs, mut rx_fs) = mpsc::unbounded_channel::<()>();
+                let config_path = config_manager_watcher.file_path().clone();
+                let config_dir = config_path.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+                let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                    if event.is_ok_and(|e| e.paths.iter().any(|p| p.file_name() == config_path.file_name())) {
+                        let _ = tx_fs.send(());
+                    }
+                }).and_then(|mut watcher| watcher.watch(&config_dir, RecursiveMode::NonRecursive).map(|_| watcher));
+                let watcher = watcher.inspect_err(|e| tracing::warn!("Config file watcher unavailable, polling instead: {}", e)).ok();
+
+                let mut interval = tokio::time::interval(Duration::from_secs(2));
+                loop {
+                    if watcher.is_some() {
+                        if rx_fs.recv().await.is_none() { break; }
+                        // One save fires several events; let the editor finish before reading
+                        tokio::time::sleep(Duration::from_millis(200)).await;
+                        while rx_fs.try_recv().is_ok() {}
+                    } else {
+                        interval.tick().await;
+                    }
+                    // An edit that fails validation keeps the running config (reload_if_changed logs why)
+                    if let Some(config) = config_manager_watcher.reload_if_changed(|c| commands::config::validate_general(&c.general)) {
+                        core::crash::set_reports_enabled(config.general.write_crash_reports);
+                        tracing::info!("Config file changed on disk; reloaded");
+                        let _ = watch_handle.emit_all("config-changed", config);
+                    }
+                }
+            });
+
             tauri::async_runtime::spawn(async move {
-                while let Some(_) = rx_save.recv().await {
-                    while let Ok(_) = rx_save.try_recv() {}
-                    tokio::time::sleep(Duration::from_millis(500)).await;
+                let debounce = Duration::from_millis(500);
+                while rx_save.recv().await.is_some() {
+                    // Every new event restarts the timer; save once the window has been still for 500ms
+                    while let Ok(Some(())) = tokio::time::timeout(debounce, rx_save.recv()).await {}
                     if let Err(e) = config_manager_saver.save() {
                         tracing::error!("Failed to auto-save window config: {}", e);
                     }
@@ -108,19 +171,19 @@ fn main() {
             }
 
             if let WindowEvent::Moved(pos) = event.event() {
-                let mut current_config = config_manager_event.get_config();
-                current_config.window.x = pos.x as f64;
-                current_config.window.y = pos.y as f64;
-                config_manager_event.update_window(current_config.window);
+                config_manager_event.update_window(|window| {
+                    window.x = pos.x as f64;
+                    window.y = pos.y as f64;
+                });
                 let _ = tx_save.send(());
             }
             
             if let WindowEvent::Resized(size) = event.event() {
                 if size.width > 0 && size.height > 0 {
-                    let mut current_config = config_manager_event.get_config();
-                    current_config.window.width = size.width as f64;
-                    current_config.window.height = size.height as f64;
-                    config_manager_event.update_window(current_config.window);
+                    config_manager_event.update_window(|window| {
+                        window.width = size.width as f64;
+                        window.height = size.height as f64;
+                    });
                     let _ = tx_save.send(());
                 }
             }
@@ -128,20 +191,55 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             commands::system::check_dependencies,
             commands::system::install_dependency,
+            commands::system::cancel_dependency_install,
+            commands::system::repair_bin_permissions,
             commands::system::sync_dependencies,
             commands::system::open_external_link,
             commands::system::close_splash,
             commands::system::get_latest_app_version, 
+            commands::system::check_connectivity,
             commands::system::show_in_folder, 
+            commands::system::get_error_log,
+            commands::system::debug_dump_state,
+            commands::system::create_debug_bundle,
+            commands::system::open_config_file,
+            commands::system::open_data_folder,
+            commands::system::get_storage_usage,
+            commands::system::clear_temp,
+            commands::system::clear_logs,
+            commands::system::get_supported_sites,
             commands::downloader::start_download,
             commands::downloader::cancel_download,
+            commands::downloader::cancel_batch,
+            commands::downloader::remove_job,
+            commands::downloader::refresh_job_metadata,
+            commands::downloader::set_job_priority,
+            commands::downloader::set_queue_download_path,
+            commands::downloader::start_queue,
+            commands::downloader::set_job_label,
+            commands::downloader::search_queue,
+            commands::downloader::resume_queue,
+            commands::downloader::pause_all,
+            commands::downloader::resume_all,
+            commands::downloader::check_already_downloaded,
+            commands::downloader::get_video_details,
+            commands::downloader::get_clipboard_url_suggestion,
             commands::downloader::expand_playlist,
+            commands::downloader::get_thumbnail,
+            commands::downloader::get_stream_url,
+            commands::downloader::estimate_batch,
+            commands::downloader::preview_format,
+            commands::downloader::preview_format_selector,
+            commands::downloader::get_audio_languages,
             commands::downloader::get_pending_jobs,
+            commands::downloader::get_current_progress,
             commands::downloader::resume_pending_jobs,
             commands::downloader::clear_pending_jobs,
             commands::config::get_app_config,
             commands::config::save_general_config,
             commands::config::save_preference_config,
+            commands::config::export_settings,
+            commands::config::import_settings,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -54,8 +54,9 @@ function App() {
         // Remove the error entry first
         removeDownload(job.jobId);
         
-        // Start a fresh download with preserved settings
-        // FORCE restrictFilenames to true for manual retries
+        // Start a fresh download with preserved settings. The filename setting is kept as-is so
+        // yt-dlp finds the .part file left in temp and continues it; filesystem errors are
+        // already retried with safe names by the backend (auto_sanitize_retry).
         startDownload(
             job.url,
             job.downloadPath,
@@ -64,7 +65,7 @@ function App() {
             job.embedMetadata || false,
             job.embedThumbnail || false,
             job.filenameTemplate || "%(title)s.%(ext)s",
-            true // <--- FORCE SAFE FILENAMES ON MANUAL RETRY
+            job.restrictFilenames || false
         );
     });
   };
@@ -1,6 +1,6 @@
 import { invoke } from "@tauri-apps/api/tauri";
 import { open } from "@tauri-apps/api/dialog";
-import { DownloadFormatPreset, AppDependencies, AppConfig, GeneralConfig, PreferenceConfig, PlaylistResult, QueuedJob } from '@/types';
+import { ActorStateDump, DownloadFormatPreset, DownloadProgressPayload, AppDependencies, BatchEstimate, CompletedEntry, ConnectivityResult, JobPriority, JobSearchResult, FormatPreview, FormatSelectorPreview, AppConfig, GeneralConfig, PreferenceConfig, PlaylistEntry, PlaylistResult, QueuedJob, StartDownloadOptions, StorageUsage, VideoDetails } from '@/types';
 
 export async function checkDependencies(): Promise<AppDependencies> {
     return await invoke("check_dependencies");
@@ -10,6 +10,15 @@ export async function installDependency(name: string): Promise<void> {
     return await invoke("install_dependency", { name });
 }
 
+// Unix only in effect: restores the execute bit on bundled binaries
+export async function repairBinPermissions(): Promise<string[]> {
+    return await invoke("repair_bin_permissions");
+}
+
+export async function cancelDependencyInstall(name: string): Promise<void> {
+    return await invoke("cancel_dependency_install", { name });
+}
+
 export async function syncDependencies(): Promise<AppDependencies> {
     return await invoke("sync_dependencies");
 }
@@ -26,10 +35,54 @@ export async function getLatestAppVersion(): Promise<string> {
     return await invoke("get_latest_app_version");
 }
 
+export async function checkConnectivity(): Promise<ConnectivityResult[]> {
+    return await invoke("check_connectivity");
+}
+
 export async function showInFolder(path: string): Promise<void> {
     return await invoke("show_in_folder", { path });
 }
 
+// Opens ~/.multiyt-dlp/config.json in the default editor; saved edits arrive as 'config-changed'
+export async function openConfigFile(): Promise<void> {
+    return await invoke("open_config_file");
+}
+
+export async function openDataFolder(): Promise<void> {
+    return await invoke("open_data_folder");
+}
+
+export async function debugDumpState(): Promise<ActorStateDump> {
+    return await invoke("debug_dump_state");
+}
+
+// Resolves to the path of the written zip
+export async function createDebugBundle(): Promise<string> {
+    return await invoke("create_debug_bundle");
+}
+
+export async function getErrorLog(maxLines?: number): Promise<string> {
+    return await invoke("get_error_log", { maxLines });
+}
+
+// Extractor names known to the installed yt-dlp (cached per session)
+export async function getSupportedSites(): Promise<string[]> {
+    return await invoke("get_supported_sites");
+}
+
+export async function getStorageUsage(): Promise<StorageUsage> {
+    return await invoke("get_storage_usage");
+}
+
+// Both return the number of bytes freed
+export async function clearTemp(): Promise<number> {
+    return await invoke("clear_temp");
+}
+
+export async function clearLogs(): Promise<number> {
+    return await invoke("clear_logs");
+}
+
 // --- Config API ---
 
 export async function getAppConfig(): Promise<AppConfig> {
@@ -44,12 +97,65 @@ export async function savePreferenceConfig(config: PreferenceConfig): Promise<vo
     return await invoke("save_preference_config", { config });
 }
 
+export async function exportSettings(includeSecrets?: boolean): Promise<string> {
+    return await invoke("export_settings", { includeSecrets });
+}
+
+// Resolves with warnings about settings that were dropped (e.g. missing folders)
+export async function importSettings(json: string): Promise<string[]> {
+    return await invoke("import_settings", { json });
+}
+
 // --- Downloader API ---
 
 export async function expandPlaylist(url: string): Promise<PlaylistResult> {
     return await invoke("expand_playlist", { url });
 }
 
+// Remote thumbnail URL, or a cached data URI when inline is set
+export async function getThumbnail(url: string, inline: boolean = false): Promise<string> {
+    return await invoke("get_thumbnail", { url, inline });
+}
+
+// Resolved CDN URLs are time-limited; use them promptly.
+export async function getStreamUrl(
+  url: string,
+  formatPreset: DownloadFormatPreset,
+  videoResolution?: string
+): Promise<string[]> {
+    return await invoke("get_stream_url", { url, formatPreset, videoResolution });
+}
+
+export async function estimateBatch(
+  urls: string[],
+  formatPreset: DownloadFormatPreset,
+  videoResolution?: string
+): Promise<BatchEstimate> {
+    return await invoke("estimate_batch", { urls, formatPreset, videoResolution });
+}
+
+export async function getAudioLanguages(url: string): Promise<string[]> {
+    return await invoke("get_audio_languages", { url });
+}
+
+export async function previewFormat(
+  url: string,
+  formatPreset: DownloadFormatPreset,
+  videoResolution?: string,
+  targetFilesize?: string
+): Promise<FormatPreview> {
+    return await invoke("preview_format", { url, formatPreset, videoResolution, targetFilesize });
+}
+
+// Offline: the -f/-S strings for these options plus a plain-language explanation.
+// Pass the unsaved general settings to preview them; the saved ones are used otherwise.
+export async function previewFormatSelector(
+  formatPreset: DownloadFormatPreset,
+  options: { videoResolution?: string; targetFilesize?: string; audioLang?: string; audioBitrate?: number; general?: GeneralConfig } = {}
+): Promise<FormatSelectorPreview> {
+    return await invoke("preview_format_selector", { formatPreset, ...options });
+}
+
 export async function startDownload(
   url: string, 
   downloadPath: string | undefined, 
@@ -58,17 +164,21 @@ export async function startDownload(
   embedMetadata: boolean,
   embedThumbnail: boolean,
   filenameTemplate: string,
-  restrictFilenames: boolean = false
+  restrictFilenames: boolean = false,
+  options: StartDownloadOptions = {}
 ): Promise<string[]> { 
   return await invoke("start_download", { 
     url, 
-    downloadPath, 
-    formatPreset,
-    videoResolution,
-    embedMetadata,
-    embedThumbnail,
-    filenameTemplate,
-    restrictFilenames
+    options: {
+      downloadPath, 
+      formatPreset,
+      videoResolution,
+      embedMetadata,
+      embedThumbnail,
+      filenameTemplate,
+      restrictFilenames,
+      ...options
+    }
   });
 }
 
@@ -76,8 +186,81 @@ export async function cancelDownload(jobId: string): Promise<void> {
   return await invoke("cancel_download", { jobId });
 }
 
+// Resolves to the cancelled job ids; emits one 'batch-cancelled'
+export async function cancelBatch(batchId: string): Promise<string[]> {
+  return await invoke("cancel_batch", { batchId });
+}
+
+// Releases held jobs (all when jobIds is omitted); resolves to how many were released
+export async function startQueue(jobIds?: string[]): Promise<number> {
+  return await invoke("start_queue", { jobIds });
+}
+
+// Tags a job for grouping/filtering; null or an empty string clears the label
+export async function setJobLabel(id: string, label: string | null): Promise<void> {
+  return await invoke("set_job_label", { id, label });
+}
+
+// Case-insensitive title/URL/label match; an empty query returns every job
+export async function searchQueue(query: string): Promise<JobSearchResult[]> {
+  return await invoke("search_queue", { query });
+}
+
+// Re-probes a queued job for its real title/thumbnail; the queue is re-sent via 'queue-state'
+export async function refreshJobMetadata(id: string): Promise<PlaylistEntry> {
+  return await invoke("refresh_job_metadata", { id });
+}
+
+// Only finished, failed or cancelled jobs can be removed
+export async function removeJob(jobId: string): Promise<void> {
+  return await invoke("remove_job", { jobId });
+}
+
+// Earlier downloads of the same video(s) in any format
+export async function checkAlreadyDownloaded(url: string): Promise<CompletedEntry[]> {
+  return await invoke("check_already_downloaded", { url });
+}
+
+// Redirects jobs that haven't started yet; returns how many were changed
+export async function setQueueDownloadPath(path: string): Promise<number> {
+  return await invoke("set_queue_download_path", { path });
+}
+
+// Reads the .info.json next to a finished download; null when none was written
+export async function getVideoDetails(path: string): Promise<VideoDetails | null> {
+  return await invoke("get_video_details", { path });
+}
+
+// Clipboard URL worth offering (not queued, not offered before), or null
+export async function getClipboardUrlSuggestion(): Promise<string | null> {
+  return await invoke("get_clipboard_url_suggestion");
+}
+
+export async function resumeQueue(): Promise<void> {
+  return await invoke("resume_queue");
+}
+
+// Suspends running downloads and holds the queue; emits 'global-paused'
+export async function pauseAll(): Promise<void> {
+  return await invoke("pause_all");
+}
+
+// Emits 'global-resumed'
+export async function resumeAll(): Promise<void> {
+  return await invoke("resume_all");
+}
+
+export async function setJobPriority(jobId: string, priority: JobPriority): Promise<void> {
+  return await invoke("set_job_priority", { jobId, priority });
+}
+
 // --- Persistence API ---
 
+// Last known progress of active jobs, used to rehydrate after a webview reload
+export async function getCurrentProgress(): Promise<DownloadProgressPayload[]> {
+    return await invoke("get_current_progress");
+}
+
 export async function getPendingJobs(): Promise<number> {
     return await invoke("get_pending_jobs");
 }
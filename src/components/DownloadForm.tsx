@@ -30,6 +30,7 @@ const formatPresets: {
   { label: 'Best MP4', value: 'best_mp4', mode: 'video' },
   { label: 'Best MKV', value: 'best_mkv', mode: 'video' },
   { label: 'Best WebM', value: 'best_webm', mode: 'video' },
+  { label: 'Subtitles Only', value: 'subtitles_only', mode: 'video' },
   { label: 'Best Audio', value: 'audio_best', mode: 'audio' },
   { label: 'MP3 Audio', value: 'audio_mp3', mode: 'audio' },
   { label: 'FLAC (Lossless)', value: 'audio_flac', mode: 'audio' },
@@ -11,6 +11,8 @@ interface InstallProgress {
     name: string;
     percentage: number;
     status: string;
+    speed?: string | null;
+    eta?: string | null;
 }
 
 export function SplashWindow() {
@@ -1,7 +1,9 @@
 import { useEffect, useState } from 'react';
 import { useAppContext } from '@/contexts/AppContext';
-import { X, Download, PartyPopper, PlayCircle, Trash2 } from 'lucide-react';
-import { openExternalLink, getPendingJobs, resumePendingJobs, clearPendingJobs } from '@/api/invoke';
+import { listen } from '@tauri-apps/api/event';
+import { X, Download, PartyPopper, PlayCircle, Trash2, AlertTriangle, FolderOpen } from 'lucide-react';
+import { openExternalLink, getPendingJobs, resumePendingJobs, clearPendingJobs, openDataFolder } from '@/api/invoke';
+import { AppPanicPayload } from '@/types';
 import { Button } from './Button';
 import { useDownloadManager } from '@/hooks/useDownloadManager';
 
@@ -10,8 +12,19 @@ export function Toast() {
     const { importResumedJobs } = useDownloadManager();
     
     const [visible, setVisible] = useState(false);
-    const [mode, setMode] = useState<'update' | 'resume' | null>(null);
+    const [mode, setMode] = useState<'update' | 'resume' | 'panic' | null>(null);
     const [pendingCount, setPendingCount] = useState(0);
+    const [panic, setPanic] = useState<AppPanicPayload | null>(null);
+
+    // A backend panic takes precedence over update/resume notices
+    useEffect(() => {
+        const unlisten = listen<AppPanicPayload>('app-panic', (event) => {
+            setPanic(event.payload);
+            setMode('panic');
+            setVisible(true);
+        });
+        return () => { unlisten.then((f) => f()); };
+    }, []);
 
     useEffect(() => {
         // Check for Resume first
@@ -60,6 +73,46 @@ export function Toast() {
         setVisible(false);
     };
 
+    if (mode === 'panic' && panic) {
+        return (
+            <div className="fixed bottom-6 right-6 z-50 animate-fade-in">
+                <div className="bg-zinc-900 border border-red-500/50 rounded-lg p-4 w-80 flex flex-col gap-3">
+                    <div className="flex justify-between items-start">
+                        <div className="flex items-center gap-2 text-red-400 font-bold">
+                            <AlertTriangle className="h-5 w-5" />
+                            <span>Something Went Wrong</span>
+                        </div>
+                        <button 
+                            onClick={() => setVisible(false)} 
+                            className="text-zinc-500 hover:text-white transition-colors"
+                        >
+                            <X className="h-4 w-4" />
+                        </button>
+                    </div>
+                    <div className="text-sm text-zinc-300">
+                        {panic.reportPath
+                            ? 'An internal error occurred. A crash report was saved; please attach it to a bug report.'
+                            : 'An internal error occurred. Enable crash reports in Settings to save details next time.'}
+                    </div>
+                    <div className="text-xs font-mono bg-black/30 p-2 rounded border border-zinc-800 text-zinc-400 break-all">
+                        {panic.message}
+                    </div>
+                    {panic.reportPath && (
+                        <Button 
+                            size="sm" 
+                            variant="secondary" 
+                            className="w-full h-8 text-xs"
+                            onClick={() => openDataFolder()}
+                        >
+                            <FolderOpen className="h-3 w-3 mr-2" />
+                            Open Report Folder
+                        </Button>
+                    )}
+                </div>
+            </div>
+        );
+    }
+
     return (
         <div className="fixed bottom-6 right-6 z-50 animate-fade-in">
             <div className="bg-zinc-900 border border-theme-cyan/50 shadow-[0_0_20px_-5px_rgba(0,242,234,0.3)] rounded-lg p-4 w-80 flex flex-col gap-3">
@@ -1,7 +1,8 @@
-import React, { useState, useEffect, useCallback } from 'react';
-import { TemplateBlock, PreferenceConfig } from '@/types';
+import React, { useState, useEffect, useCallback, useRef } from 'react';
+import { TemplateBlock, PreferenceConfig, GeneralConfig, AppConfig } from '@/types';
 import { getAppConfig, saveGeneralConfig, savePreferenceConfig, checkDependencies, getLatestAppVersion } from '@/api/invoke';
 import { getVersion } from '@tauri-apps/api/app';
+import { listen } from '@tauri-apps/api/event';
 
 interface AppContextType {
   // State
@@ -70,6 +71,7 @@ export const AppProvider = ({ children }: { children: React.ReactNode }) => {
   const [defaultDownloadPath, _setDownloadPath] = useState<string | null>(null);
   const [filenameTemplateBlocks, _setTemplateBlocks] = useState<TemplateBlock[]>(DEFAULT_TEMPLATE_BLOCKS);
   const [preferences, _setPreferences] = useState<PreferenceConfig>(DEFAULT_PREFS);
+  const generalConfigRef = useRef<GeneralConfig | null>(null);
   
   // Cookie State
   const [cookiesPath, _setCookiesPath] = useState<string | null>(null);
@@ -116,35 +118,46 @@ export const AppProvider = ({ children }: { children: React.ReactNode }) => {
     }
   };
 
+  const applyConfig = (config: AppConfig) => {
+    generalConfigRef.current = config.general;
+
+    if (config.general.download_path) _setDownloadPath(config.general.download_path);
+    if (config.general.cookies_path) _setCookiesPath(config.general.cookies_path);
+    if (config.general.cookies_from_browser) _setCookiesBrowser(config.general.cookies_from_browser);
+
+    _setMaxConcurrentDownloads(config.general.max_concurrent_downloads);
+    _setMaxTotalInstances(config.general.max_total_instances);
+    _setLogLevel(config.general.log_level || 'info');
+    _setCheckForUpdates(config.general.check_for_updates);
+
+    if (config.general.template_blocks_json) {
+        try {
+            const parsed = JSON.parse(config.general.template_blocks_json);
+            _setTemplateBlocks(parsed);
+        } catch(e) { console.warn("Failed to parse blocks", e); }
+    }
+
+    _setPreferences({ ...DEFAULT_PREFS, ...config.preferences });
+  };
+
+  // config.json edited by hand (see openConfigFile) and reloaded by the backend
+  useEffect(() => {
+    const unlisten = listen<AppConfig>('config-changed', (event) => applyConfig(event.payload));
+    return () => { unlisten.then(f => f()); };
+  }, []);
+
   useEffect(() => {
     const load = async () => {
       try {
         const config = await getAppConfig();
-        
-        if (config.general.download_path) _setDownloadPath(config.general.download_path);
-        if (config.general.cookies_path) _setCookiesPath(config.general.cookies_path);
-        if (config.general.cookies_from_browser) _setCookiesBrowser(config.general.cookies_from_browser);
-
-        _setMaxConcurrentDownloads(config.general.max_concurrent_downloads);
-        _setMaxTotalInstances(config.general.max_total_instances);
-        _setLogLevel(config.general.log_level || 'info');
-        _setCheckForUpdates(config.general.check_for_updates);
-
-        if (config.general.template_blocks_json) {
-            try {
-                const parsed = JSON.parse(config.general.template_blocks_json);
-                _setTemplateBlocks(parsed);
-            } catch(e) { console.warn("Failed to parse blocks", e); }
-        }
-
-        _setPreferences({ ...DEFAULT_PREFS, ...config.preferences });
+        applyConfig(config);
         
         const deps = await checkDependencies();
         if (!deps.js_runtime.available) {
             setIsJsRuntimeMissing(true);
         }
 
-        if (config.general.check_for_updates) {
+        if (config.general.check_for_updates && !config.general.offline_mode) {
             checkAppUpdate();
         } else {
             getVersion().then(v => setCurrentVersion(v));
@@ -179,7 +192,9 @@ export const AppProvider = ({ children }: { children: React.ReactNode }) => {
       cPath: string | null,
       cBrowser: string | null
     ) => {
-      saveGeneralConfig({
+      // Spread the last known backend config so fields without UI controls survive the save
+      const next: GeneralConfig = {
+        ...(generalConfigRef.current as GeneralConfig),
         download_path: path,
         filename_template: getTemplateString(blocks),
         template_blocks_json: JSON.stringify(blocks),
@@ -189,7 +204,9 @@ export const AppProvider = ({ children }: { children: React.ReactNode }) => {
         check_for_updates: updates,
         cookies_path: cPath,
         cookies_from_browser: cBrowser
-      }).catch(e => console.error("Failed to save general config:", e));
+      };
+      generalConfigRef.current = next;
+      saveGeneralConfig(next).catch(e => console.error("Failed to save general config:", e));
   };
 
   const setDefaultDownloadPath = (path: string) => {
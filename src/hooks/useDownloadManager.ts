@@ -1,7 +1,7 @@
 import { useState, useEffect, useCallback } from 'react';
 import { listen } from '@tauri-apps/api/event';
-import { Download, DownloadCompletePayload, DownloadErrorPayload, BatchProgressPayload, DownloadFormatPreset, QueuedJob } from '@/types';
-import { startDownload as apiStartDownload, cancelDownload as apiCancelDownload } from '@/api/invoke';
+import { BatchCancelledPayload, Download, DownloadCancelledPayload, DownloadCompletePayload, DownloadSkippedPayload, DownloadProgressPayload, DownloadErrorPayload, BatchProgressPayload, DownloadFormatPreset, QueuedJob } from '@/types';
+import { startDownload as apiStartDownload, cancelDownload as apiCancelDownload, removeJob as apiRemoveJob, getCurrentProgress } from '@/api/invoke';
 
 export function useDownloadManager() {
   const [downloads, setDownloads] = useState<Map<string, Download>>(new Map());
@@ -33,20 +33,26 @@ export function useDownloadManager() {
   };
 
   useEffect(() => {
+    const toUpdates = (payloads: DownloadProgressPayload[]) => payloads.map(u => ({
+        jobId: u.jobId,
+        data: {
+            status: 'downloading' as const,
+            progress: u.percentage,
+            speed: u.speed,
+            eta: u.eta,
+            filename: u.filename,
+            phase: u.phase
+        }
+    }));
+
+    // Rehydrate in-flight jobs if the webview was reloaded mid-download
+    getCurrentProgress()
+        .then(payloads => { if (payloads.length > 0) updateDownloadsBatch(toUpdates(payloads)); })
+        .catch(e => console.warn("Failed to restore progress:", e));
+
     // UPDATED: Listen for Batch Events instead of single events
     const unlistenProgress = listen<BatchProgressPayload>('download-progress-batch', (event) => {
-        const updates = event.payload.updates.map(u => ({
-            jobId: u.jobId,
-            data: {
-                status: 'downloading' as const,
-                progress: u.percentage,
-                speed: u.speed,
-                eta: u.eta,
-                filename: u.filename,
-                phase: u.phase
-            }
-        }));
-        updateDownloadsBatch(updates);
+        updateDownloadsBatch(toUpdates(event.payload.updates));
     });
 
     const unlistenComplete = listen<DownloadCompletePayload>('download-complete', (event) => {
@@ -58,6 +64,15 @@ export function useDownloadManager() {
       });
     });
 
+    const unlistenSkipped = listen<DownloadSkippedPayload>('download-skipped', (event) => {
+      updateDownload(event.payload.jobId, {
+        status: 'completed',
+        progress: 100,
+        outputPath: event.payload.outputPath,
+        phase: 'Already exists, skipped',
+      });
+    });
+
     const unlistenError = listen<DownloadErrorPayload>('download-error', (event) => {
       updateDownload(event.payload.jobId, {
         status: 'error',
@@ -65,10 +80,21 @@ export function useDownloadManager() {
       });
     });
 
+    const unlistenCancelled = listen<DownloadCancelledPayload>('download-cancelled', (event) => {
+      updateDownload(event.payload.jobId, { status: 'cancelled' });
+    });
+
+    const unlistenBatchCancelled = listen<BatchCancelledPayload>('batch-cancelled', (event) => {
+      updateDownloadsBatch(event.payload.jobIds.map(jobId => ({ jobId, data: { status: 'cancelled' } })));
+    });
+
     return () => {
       unlistenProgress.then((f) => f());
       unlistenComplete.then((f) => f());
+      unlistenSkipped.then((f) => f());
       unlistenError.then((f) => f());
+      unlistenCancelled.then((f) => f());
+      unlistenBatchCancelled.then((f) => f());
     };
   }, []);
 
@@ -141,6 +167,14 @@ export function useDownloadManager() {
       });
   }, []);
 
+  // Backend resumed leftover jobs on its own (auto_resume_on_startup)
+  useEffect(() => {
+    const unlistenResumed = listen<QueuedJob[]>('jobs-resumed', (event) => {
+      importResumedJobs(event.payload);
+    });
+    return () => { unlistenResumed.then((f) => f()); };
+  }, [importResumedJobs]);
+
   const cancelDownload = useCallback(async (jobId: string) => {
     try {
       await apiCancelDownload(jobId);
@@ -151,7 +185,13 @@ export function useDownloadManager() {
     }
   }, []);
 
-  const removeDownload = useCallback((jobId: string) => {
+  const removeDownload = useCallback(async (jobId: string) => {
+      try {
+          await apiRemoveJob(jobId);
+      } catch (error) {
+          console.error('Failed to remove download:', error);
+          return;
+      }
       setDownloads((prev) => {
           const newMap = new Map(prev);
           newMap.delete(jobId);
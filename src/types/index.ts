@@ -1,13 +1,83 @@
 export interface GeneralConfig {
   download_path: string | null;
+  audio_download_path: string | null;
+  video_download_path: string | null;
+  min_free_space: string | null;
+  download_path_pool: string[];
+  download_path_pool_min_free: string;
   filename_template: string;
   template_blocks_json: string | null;
+  upload_date_format: string | null;
+  date_subfolder_format: string | null;
   max_concurrent_downloads: number;
   max_total_instances: number;
+  // 0 = no per-host limit
+  max_concurrent_per_host: number;
+  max_concurrent_probes: number;
+  channel_include_videos: boolean;
+  channel_include_shorts: boolean;
+  channel_include_streams: boolean;
   log_level: string;
   check_for_updates: boolean;
+  offline_mode: boolean;
   cookies_path: string | null;
   cookies_from_browser: string | null;
+  // Linux only: basictext, gnomekeyring, kwallet, kwallet5 or kwallet6
+  cookies_browser_keyring: string | null;
+  cookies_text: string | null;
+  refresh_browser_cookies: boolean;
+  global_max_height: number | null;
+  format_sort: string | null;
+  prefer_progressive: boolean;
+  // Applies to MP3 and FLAC output only
+  normalize_audio: boolean;
+  subtitle_langs: string;
+  hdr_preference: HdrPreference;
+  speed_units: SpeedUnits;
+  redownload_existing: boolean;
+  // Skip jobs whose predicted output file already exists, checked when the job starts (it holds a
+  // download slot during the probe); ignored when redownload_existing is on
+  skip_existing: boolean;
+  // 'jpg' | 'png' | 'webp'; null picks jpg for MP4/MKV automatically
+  thumbnail_format: string | null;
+  auto_resume_on_startup: boolean;
+  write_info_json: boolean;
+  enable_http_api: boolean;
+  http_api_bind: string;
+  http_api_token: string | null;
+  proxy: string | null;
+  ytdlp_config_path: string | null;
+  ffmpeg_path: string | null;
+  rate_limit: string | null;
+  fragment_retries: number;
+  buffer_size: string | null;
+  // 1-16; parallel fragments for HLS/DASH only
+  concurrent_fragments: number;
+  // e.g. '10M'; ranged requests for single progressive files, which helps on CDNs that throttle per connection
+  http_chunk_size: string | null;
+  rate_schedule: RateWindow[];
+  verify_output: boolean;
+  post_hook_command: string | null;
+  // Removes the file once post_hook_command exits 0; a failed or missing hook never deletes
+  delete_after_hook: boolean;
+  stop_queue_on_error: boolean;
+  persist_global_pause: boolean;
+  reject_duplicate_urls: boolean;
+  enable_debug_tools: boolean;
+  write_crash_reports: boolean;
+  // Retry with restricted (ASCII-only) filenames after a filesystem error
+  auto_sanitize_retry: boolean;
+  // Cheap truncation check against yt-dlp's reported sizes (no ffprobe needed)
+  verify_size: boolean;
+  // Block YouTube downloads without Deno/Bun/Node rather than emitting 'js-runtime-missing' as a warning
+  require_js_runtime: boolean;
+}
+
+// Hours are local time; end_hour is exclusive and may be smaller than start_hour (wraps midnight)
+export interface RateWindow {
+  start_hour: number;
+  end_hour: number;
+  limit: string | null;
 }
 
 export interface PreferenceConfig {
@@ -40,10 +110,42 @@ export interface DependencyInfo {
     path: string | null;
 }
 
+export interface CompletedEntry {
+  video_id: string;
+  format_preset: DownloadFormatPreset;
+  output_path: string;
+  completed_at: number;
+  label?: string | null;
+}
+
+export interface StorageUsage {
+  temp_bytes: number;
+  logs_bytes: number;
+  bin_bytes: number;
+  history_bytes: number;
+}
+
+// Emitted as 'dependency-sync-result' once per dependency during startup sync
+export interface DependencySyncResult {
+  name: string;
+  success: boolean;
+  error: string | null;
+}
+
+export interface ConnectivityResult {
+  target: string;
+  url: string;
+  reachable: boolean;
+  status: number | null;
+  latency_ms: number | null;
+  error: string | null;
+}
+
 export interface AppDependencies {
   yt_dlp: DependencyInfo;
   ffmpeg: DependencyInfo;
   js_runtime: DependencyInfo;
+  js_runtime_working: boolean;
 }
 
 export type AppError = {
@@ -53,6 +155,13 @@ export type AppError = {
   JobAlreadyExists?: string;
 };
 
+export type JobPriority = 'normal' | 'high';
+
+export type HdrPreference = 'keep' | 'prefer_sdr' | 'prefer_hdr';
+
+// binary: KiB/s, MiB/s; decimal: KB/s, MB/s
+export type SpeedUnits = 'binary' | 'decimal';
+
 export type DownloadFormatPreset = 
   | 'best' 
   | 'best_mp4' 
@@ -61,7 +170,8 @@ export type DownloadFormatPreset =
   | 'audio_best' 
   | 'audio_mp3'
   | 'audio_flac'
-  | 'audio_m4a';
+  | 'audio_m4a'
+  | 'subtitles_only';
 
 export interface DownloadProgressPayload {
   jobId: string;
@@ -80,11 +190,152 @@ export interface BatchProgressPayload {
 export interface DownloadCompletePayload {
   jobId: string;
   outputPath: string;
+  downloadDir: string;
+  filesize: number;
+  formatId: string | null;
+  container: string;
+  // Set when the job succeeded without writing a file, e.g. no subtitles in the requested languages
+  note: string | null;
+  label: string | null;
+}
+
+export type ErrorCode = 'AGE_RESTRICTED' | 'CORRUPT_OUTPUT' | 'COOKIE_DATABASE_LOCKED' | 'BOT_CHECK' | 'SIZE_MISMATCH' | 'COOKIE_DECRYPT_FAILED' | 'TARGET_NOT_WRITABLE' | 'JS_RUNTIME_MISSING';
+
+// 'download-skipped': skip_existing found the file already in the target folder
+export interface DownloadSkippedPayload {
+  jobId: string;
+  outputPath: string;
+  reason: string;
+}
+
+export interface DownloadCancelledPayload {
+  jobId: string;
+}
+
+export interface BatchCancelledPayload {
+  batchId: string;
+  jobIds: string[];
 }
 
 export interface DownloadErrorPayload {
   jobId: string;
   error: string;
+  errorCode?: ErrorCode;
+  hint?: string;
+}
+
+export interface QueueHaltedPayload {
+  failedJobId: string;
+  pending: number;
+}
+
+// 'app-panic': the backend hit a bug; reportPath is set when write_crash_reports is on
+export interface AppPanicPayload {
+  message: string;
+  reportPath: string | null;
+}
+
+// 'queue-paused-disk-full' / 'queue-disk-space-resumed'
+export interface DiskSpacePayload {
+  path: string;
+  freeBytes: number;
+  requiredBytes: number;
+}
+
+// debug_dump_state snapshot (needs enable_debug_tools)
+export interface ActorStateDump {
+  active_network_jobs: number;
+  network_job_ids: string[];
+  active_process_instances: number;
+  completed_session_count: number;
+  queue_halted: boolean;
+  disk_space_paused: boolean;
+  global_paused: boolean;
+  suspended_job_ids: string[];
+  queue_len: number;
+  persistence_registry_len: number;
+  open_batches: number;
+  jobs: {
+    id: string;
+    url: string;
+    pid: number | null;
+    status: JobStatus;
+    progress: number;
+    output_path: string | null;
+    title: string | null;
+  }[];
+}
+
+export type JobStatus = 'Pending' | 'Downloading' | 'Completed' | 'Skipped' | 'Cancelled' | 'Error';
+
+export interface JobSearchResult {
+  id: string;
+  title: string | null;
+  label: string | null;
+  url: string;
+  status: JobStatus;
+}
+
+// 'global-paused' / 'global-resumed'
+export interface GlobalPausePayload {
+  affectedJobs: number;
+}
+
+export interface QueueStatePayload {
+  pending: QueuedJob[];
+}
+
+export interface VideoDetails {
+  title: string | null;
+  description: string | null;
+  uploader: string | null;
+  upload_date: string | null;
+  duration: number | null;
+  view_count: number | null;
+  like_count: number | null;
+  tags: string[];
+  webpage_url: string | null;
+}
+
+export interface SlowDownloadWarningPayload {
+  jobId: string;
+  speedBps: number;
+  jsRuntime: string | null;
+  message: string;
+}
+
+// 'music-metadata-missing': a music-library track lacks artist/album tags and lands in an 'Unknown ...' folder
+export interface MusicMetadataMissingPayload {
+  jobId: string;
+  missing: string[];
+  message: string;
+}
+
+// 'js-runtime-missing': a YouTube job started without Deno/Bun/Node; blocked when require_js_runtime is on
+export interface JsRuntimeMissingPayload {
+  jobId: string;
+  blocked: boolean;
+  message: string;
+}
+
+// 'filenames-sanitized': a filesystem error triggered the auto_sanitize_retry
+export interface FilenamesSanitizedPayload {
+  jobId: string;
+  message: string;
+}
+
+// 'file-cleaned': delete_after_hook removed the file after a successful hook
+export interface FileCleanedPayload {
+  jobId: string;
+  path: string;
+}
+
+export interface PostHookResultPayload {
+  jobId: string;
+  success: boolean;
+  exitCode: number | null;
+  stdout: string;
+  stderr: string;
 }
 
 export type DownloadStatus = 'pending' | 'downloading' | 'completed' | 'error' | 'cancelled';
@@ -120,6 +371,98 @@ export interface QueuedJob {
   embed_thumbnail: boolean;
   filename_template: string;
   restrict_filenames: boolean;
+  split_audio_chapters?: boolean;
+  target_filesize?: string | null;
+  metadata_overrides?: [string, string][];
+  write_source_url?: boolean;
+  download_storyboard?: boolean;
+  external_subtitle?: string | null;
+  transcode_profile?: TranscodeProfile | null;
+  write_comments?: boolean;
+  music_library?: boolean;
+  live_from_start?: boolean;
+  wait_for_premiere?: boolean;
+  premiere_check_interval?: number | null;
+  priority?: JobPriority;
+  video_id?: string | null;
+  audio_lang?: string | null;
+  audio_bitrate?: number | null;
+  batch_id?: string | null;
+  batch_index?: number;
+  batch_size?: number;
+  batch_title?: string | null;
+  write_m3u?: boolean;
+  proxy?: string | null;
+  title?: string | null;
+  thumbnail?: string | null;
+  // Staged with startPaused; waits for startQueue
+  held?: boolean;
+  // User tag set with setJobLabel
+  label?: string | null;
+}
+
+export type TranscodeCodec = 'h264' | 'h265' | 'vp9';
+
+// h264/h265 produce MP4, vp9 WebM
+export interface TranscodeProfile {
+  codec: TranscodeCodec;
+  // Downscale only, 144-4320
+  max_height?: number | null;
+  // 0-63; codec default when unset
+  crf?: number | null;
+  // kbps, 32-512 (default 160)
+  audio_bitrate?: number | null;
+  keep_original?: boolean;
+}
+
+// Optional per-job switches for start_download; omitted keys use backend defaults
+export interface StartDownloadOptions {
+  splitAudioChapters?: boolean;
+  targetFilesize?: string;
+  // Applied only when embedMetadata is on; fields: title, artist, album, album_artist, genre, date, track, comment
+  metadataOverrides?: [string, string][];
+  writeSourceUrl?: boolean;
+  // Save the best storyboard (thumbnail sprite sheet) next to the file, when the site has one
+  downloadStoryboard?: boolean;
+  // Path to the user's own .srt/.ass/.vtt, embedded after the download; single videos only
+  externalSubtitle?: string;
+  // Re-encode with ffmpeg after the download finishes
+  transcodeProfile?: TranscodeProfile;
+  // Saves comments into the .info.json sidecar; can add minutes per video and many MB for popular ones
+  writeComments?: boolean;
+  // Audio presets only: embeds tags and a jpg cover and files tracks as 'Artist/Album/NN - Title';
+  // overrides embedMetadata, embedThumbnail and the filename template
+  musicLibrary?: boolean;
+  liveFromStart?: boolean;
+  // Poll an upcoming premiere until it goes live
+  waitForPremiere?: boolean;
+  // Seconds between checks, 10-86400 (default 60)
+  premiereCheckInterval?: number;
+  priority?: JobPriority;
+  audioLang?: string;
+  // kbps, 32-320; ignored for FLAC
+  audioBitrate?: number;
+  // Writes '<playlist>.m3u8' once the whole batch has finished
+  writeM3u?: boolean;
+  proxy?: string;
+  // Queue without starting; release later with startQueue
+  startPaused?: boolean;
+  // 1-based, inclusive bounds on the expanded playlist; start must not exceed end
+  playlistStart?: number;
+  playlistEnd?: number;
+}
+
+export interface FormatSelectorPreview {
+    args: string[];
+    format: string | null;
+    sort: string | null;
+    explanation: string;
+}
+
+export interface FormatPreview {
+    format_id: string;
+    format: string;
+    filesize: number | null;
 }
 
 export type TemplateBlockType = 'variable' | 'separator' | 'text';
@@ -135,8 +478,22 @@ export interface PlaylistEntry {
     id?: string;
     url: string;
     title: string;
+    thumbnail?: string | null;
+    // yt-dlp extractor, e.g. "Youtube" or "Generic"
+    extractor?: string | null;
+}
+
+export interface BatchEstimate {
+    total_bytes: number;
+    entry_count: number;
+    est_seconds: number | null;
+    incomplete_estimate: boolean;
 }
 
 export interface PlaylistResult {
+    title: string | null;
     entries: PlaylistEntry[];
+    // Deleted/private entries that were skipped
+    unavailable: string[];
+    extractor?: string | null;
 }
\ No newline at end of file